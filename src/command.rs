@@ -93,8 +93,8 @@ impl VariableSubstitution {
             marker,
             variable_name,
         } = self;
-        match interpreter.get_variable(&variable_name.to_string()) {
-            Some(variable_value) => Ok(variable_value.clone()),
+        match interpreter.get_variable(&variable_name.to_string(), variable_name.span())? {
+            Some(variable_value) => Ok(variable_value),
             None => {
                 let marker = marker.as_char();
                 let name_str = variable_name.to_string();
@@ -114,6 +114,10 @@ impl VariableSubstitution {
     }
 }
 
+/// The most token trees a command such as `[!range! ..]` or `[!cartesian! ..]` can generate, so
+/// that a mistake such as `0..u64::MAX` errors rather than hanging the compiler.
+pub(crate) const MAX_GENERATED_LENGTH: u128 = 65_536;
+
 pub(crate) struct CommandArgumentStream {
     tokens: Tokens,
 }
@@ -131,12 +135,16 @@ impl CommandArgumentStream {
         Ok(concat_recursive(interpreted))
     }
 
+    pub(crate) fn interpret(self, interpreter: &mut Interpreter) -> Result<TokenStream> {
+        interpreter.interpret_tokens(self.tokens)
+    }
+
     pub(crate) fn tokens(self) -> Tokens {
         self.tokens
     }
 }
 
-fn concat_recursive(arguments: TokenStream) -> String {
+pub(crate) fn concat_recursive(arguments: TokenStream) -> String {
     fn concat_recursive_internal(output: &mut String, arguments: TokenStream) {
         for token_tree in arguments {
             match token_tree {
@@ -0,0 +1,142 @@
+use crate::internal_prelude::*;
+
+/// A closure, such as `[!fn! (#a #b) { .. }]`.
+///
+/// The value of a closure is its own `[!fn! ..]` token stream, so it can be stored in a variable
+/// like any other value. Its body is kept uninterpreted until it is called, and is interpreted
+/// against the state of the interpreter at that point.
+struct Closure {
+    parameters_group: Group,
+    parameters: Vec<Ident>,
+    body: Group,
+}
+
+impl Closure {
+    /// Parses the tokens after `[!fn!`, i.e. `(#a #b) { .. }`
+    fn parse(mut tokens: Tokens, span: Span) -> Result<Self> {
+        const EXPECTED: &str = "A closure is expected to look like [!fn! (#a #b) { .. }]";
+        let parameters_group = match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => group,
+            _ => return Err(span.error(EXPECTED)),
+        };
+        let mut parameter_tokens = Tokens::new(parameters_group.stream());
+        let mut parameters = Vec::new();
+        while parameter_tokens.peek().is_some() {
+            match parse_variable(&mut parameter_tokens) {
+                Some(parameter) => parameters.push(parameter),
+                None => return Err(parameters_group.span().error(EXPECTED)),
+            }
+        }
+        let body = match tokens.next() {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group,
+            _ => return Err(span.error(EXPECTED)),
+        };
+        if tokens.next().is_some() {
+            return Err(span.error(EXPECTED));
+        }
+        Ok(Self {
+            parameters_group,
+            parameters,
+            body,
+        })
+    }
+
+    /// Parses a closure value, as output by [`Closure::into_token_stream`].
+    fn parse_value(token_tree: Option<TokenTree>, span: Span) -> Option<Result<Self>> {
+        let group = match token_tree {
+            Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => group,
+            _ => return None,
+        };
+        let mut tokens = Tokens::new(group.stream());
+        tokens.next_as_punct_matching('!')?;
+        match tokens.next_as_ident() {
+            Some(ident) if ident == FnCommand::COMMAND_NAME => {}
+            _ => return None,
+        }
+        tokens.next_as_punct_matching('!')?;
+        Some(Self::parse(tokens, span))
+    }
+
+    fn into_token_stream(self, span: Span) -> TokenStream {
+        let mut closure_tokens = TokenStream::new();
+        closure_tokens.extend([
+            TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+            TokenTree::Ident(Ident::new(FnCommand::COMMAND_NAME, span)),
+            TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+            TokenTree::Group(self.parameters_group),
+            TokenTree::Group(self.body),
+        ]);
+        let mut closure = Group::new(Delimiter::Bracket, closure_tokens);
+        closure.set_span(span);
+        TokenStream::from(TokenTree::Group(closure))
+    }
+}
+
+pub(crate) struct FnCommand;
+
+impl CommandDefinition for FnCommand {
+    const COMMAND_NAME: &'static str = "fn";
+
+    fn execute(
+        _interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let closure = Closure::parse(argument.tokens(), command_span)?;
+        Ok(closure.into_token_stream(command_span))
+    }
+}
+
+pub(crate) struct CallCommand;
+
+impl CommandDefinition for CallCommand {
+    const COMMAND_NAME: &'static str = "call";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        // Each item is interpreted separately, so that a variable or command whose value is
+        // several token trees is still passed as a single argument
+        let mut source_tokens = argument.tokens();
+        let mut arguments = Vec::new();
+        loop {
+            match parse_next_item(&mut source_tokens)? {
+                NextItem::EndOfStream => break,
+                NextItem::Leaf(token_tree) => arguments.push(TokenStream::from(token_tree)),
+                item => arguments.push(interpreter.interpret_item(item)?),
+            }
+        }
+        let mut arguments = arguments.into_iter();
+        let closure = arguments.next().and_then(|closure| {
+            let mut closure_tokens = Tokens::new(closure);
+            let closure = closure_tokens.next();
+            if closure_tokens.peek().is_some() {
+                return None;
+            }
+            Closure::parse_value(closure, command_span)
+        });
+        let closure = match closure {
+            Some(closure) => closure?,
+            None => {
+                return Err(command_span.error(
+                    "A call is expected to look like [!call! #closure arg1 arg2 ..], where #closure was created with [!fn! ..]",
+                ));
+            }
+        };
+        if arguments.len() != closure.parameters.len() {
+            return Err(command_span.error(format!(
+                "The closure expects {} argument/s, but {} were provided. Each argument is a single token tree, variable or command, so wrap any longer arguments in a group.",
+                closure.parameters.len(),
+                arguments.len(),
+            )));
+        }
+        let bindings = closure
+            .parameters
+            .iter()
+            .map(|parameter| parameter.to_string())
+            .zip(arguments);
+        interpreter.interpret_call(bindings, Tokens::new(closure.body.stream()), command_span)
+    }
+}
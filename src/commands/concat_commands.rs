@@ -127,6 +127,199 @@ impl CommandDefinition for LiteralCommand {
     }
 }
 
+//===============================
+// Literal introspection commands
+//===============================
+
+/// Parses an integer or float literal, with an optional leading `-` (a negative number is two
+/// token trees). Returns the sign tokens, and the parsed literal.
+fn parse_numeric_literal(stream: TokenStream, span: Span) -> Result<(TokenStream, Lit)> {
+    let mut tokens = Tokens::new(stream);
+    let sign = match tokens.peek() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '-' => {
+            tokens.next().into_iter().collect()
+        }
+        _ => TokenStream::new(),
+    };
+    let token_tree = parse_single_token_tree(iter::from_fn(|| tokens.next()).collect(), span)?;
+    if let TokenTree::Literal(literal) = &token_tree {
+        if let lit @ (Lit::Int(_) | Lit::Float(_)) = Lit::new(literal.clone()) {
+            return Ok((sign, lit));
+        }
+    }
+    Err(token_tree.span().error(format!(
+        "Expected an integer or float literal, but found `{}`",
+        token_tree
+    )))
+}
+
+/// Returns the literal's representation without its suffix, and the suffix.
+fn split_suffix(lit: &Lit) -> (String, String) {
+    let (representation, suffix) = match lit {
+        Lit::Int(lit_int) => (lit_int.to_string(), lit_int.suffix().to_string()),
+        Lit::Float(lit_float) => (lit_float.to_string(), lit_float.suffix().to_string()),
+        _ => unreachable!("Only numeric literals are parsed"),
+    };
+    let unsuffixed = representation[..representation.len() - suffix.len()].to_string();
+    (unsuffixed, suffix)
+}
+
+/// Outputs the unsuffixed representation of a numeric literal. A float such as `1f32` (which syn
+/// parses as an integer literal with a float suffix) is given a `.0` so that it remains a float.
+fn unsuffixed_representation(lit: &Lit) -> String {
+    let (mut unsuffixed, suffix) = split_suffix(lit);
+    let is_float = matches!(lit, Lit::Float(_)) || suffix == "f32" || suffix == "f64";
+    if is_float && !unsuffixed.contains(['.', 'e', 'E']) {
+        unsuffixed.push_str(".0");
+    }
+    unsuffixed
+}
+
+pub(crate) struct UnsuffixedCommand;
+
+impl CommandDefinition for UnsuffixedCommand {
+    const COMMAND_NAME: &'static str = "unsuffixed";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (mut output, lit) =
+            parse_numeric_literal(argument.interpret(interpreter)?, command_span)?;
+        let literal = parse_literal(&unsuffixed_representation(&lit), command_span)?;
+        output.extend(iter::once(TokenTree::Literal(literal)));
+        Ok(output)
+    }
+}
+
+pub(crate) struct SuffixOfCommand;
+
+impl CommandDefinition for SuffixOfCommand {
+    const COMMAND_NAME: &'static str = "suffix_of";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (_, lit) = parse_numeric_literal(argument.interpret(interpreter)?, command_span)?;
+        Ok(match split_suffix(&lit) {
+            (_, suffix) if suffix.is_empty() => TokenStream::new(),
+            (_, suffix) => TokenTree::Ident(parse_ident(&suffix, command_span)?).into(),
+        })
+    }
+}
+
+pub(crate) struct WithSuffixCommand;
+
+impl CommandDefinition for WithSuffixCommand {
+    const COMMAND_NAME: &'static str = "with_suffix";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut token_trees: Vec<TokenTree> =
+            argument.interpret(interpreter)?.into_iter().collect();
+        let suffix = match token_trees.pop() {
+            Some(TokenTree::Ident(suffix)) => suffix,
+            _ => return Err(command_span.error(
+                "Expected a numeric literal followed by a suffix, such as `[!with_suffix! 5 u32]`",
+            )),
+        };
+        let (mut output, lit) =
+            parse_numeric_literal(token_trees.into_iter().collect(), command_span)?;
+        let suffix_name = suffix.to_string();
+        let (unsuffixed, _) = split_suffix(&lit);
+        match (IntegerKind::from_suffix(&suffix_name), &lit) {
+            (Some(kind), Lit::Int(_)) => {
+                // Check the value fits in the new type
+                let literal = parse_literal(&unsuffixed, command_span)?;
+                if let Some(mut integer) = IntegerValue::parse(&literal)? {
+                    if !output.is_empty() {
+                        integer.value = -integer.value;
+                    }
+                    integer.kind = Some(kind);
+                    integer.to_literal(suffix.span())?;
+                }
+            }
+            (None, _) if suffix_name == "f32" || suffix_name == "f64" => {
+                if ["0x", "0o", "0b"]
+                    .iter()
+                    .any(|prefix| unsuffixed.starts_with(prefix))
+                {
+                    return Err(suffix.span().error(format!(
+                        "A hex, octal or binary literal can't have the float suffix `{}`",
+                        suffix_name
+                    )));
+                }
+            }
+            (Some(_), _) => {
+                return Err(suffix.span().error(format!(
+                    "A float literal can't have the integer suffix `{}`",
+                    suffix_name
+                )))
+            }
+            (None, _) => {
+                return Err(suffix.span().error(format!(
+                    "`{}` is not a supported numeric suffix",
+                    suffix_name
+                )))
+            }
+        }
+        let literal = parse_literal(&format!("{}{}", unsuffixed, suffix_name), command_span)?;
+        output.extend(iter::once(TokenTree::Literal(literal)));
+        Ok(output)
+    }
+}
+
+/// Decodes the bytes of a byte or byte string literal, which must all be ASCII, as other bytes
+/// don't unambiguously correspond to a char.
+fn ascii_bytes_to_string(bytes: &[u8], token_tree: &TokenTree) -> Result<String> {
+    match bytes.iter().find(|byte| !byte.is_ascii()) {
+        Some(byte) => Err(token_tree.span().error(format!(
+            "The byte literal contains the non-ASCII byte \\x{:02x}, which can't be decoded",
+            byte
+        ))),
+        None => Ok(bytes.iter().map(|byte| char::from(*byte)).collect()),
+    }
+}
+
+pub(crate) struct LiteralValueCommand;
+
+impl CommandDefinition for LiteralValueCommand {
+    const COMMAND_NAME: &'static str = "literal_value";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let token_tree = parse_single_token_tree(argument.interpret(interpreter)?, command_span)?;
+        let lit = match &token_tree {
+            TokenTree::Literal(literal) => Some(Lit::new(literal.clone())),
+            _ => None,
+        };
+        let value = match lit {
+            Some(Lit::Str(lit_str)) => lit_str.value(),
+            Some(Lit::Char(lit_char)) => lit_char.value().to_string(),
+            Some(Lit::Byte(lit_byte)) => ascii_bytes_to_string(&[lit_byte.value()], &token_tree)?,
+            Some(Lit::ByteStr(lit_byte_str)) => {
+                ascii_bytes_to_string(&lit_byte_str.value(), &token_tree)?
+            }
+            _ => {
+                return Err(token_tree.span().error(format!(
+                    "Expected a string, char, byte or byte string literal, but found `{}`",
+                    token_tree
+                )))
+            }
+        };
+        Ok(TokenTree::Literal(string_literal(&value, command_span)).into())
+    }
+}
+
 //===========================
 // String conversion commands
 //===========================
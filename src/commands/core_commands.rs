@@ -26,6 +26,206 @@ impl CommandDefinition for SetCommand {
     }
 }
 
+pub(crate) struct SetLazyCommand;
+
+impl CommandDefinition for SetLazyCommand {
+    const COMMAND_NAME: &'static str = "set_lazy";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut argument_tokens = argument.tokens();
+        let variable_name = match parse_variable_set(&mut argument_tokens) {
+            Some(ident) => ident.to_string(),
+            None => {
+                return Err(command_span
+                    .error("A set_lazy call is expected to start with `#variable_name = ..`"));
+            }
+        };
+
+        // The tokens are stored uninterpreted, and are interpreted on each substitution
+        interpreter.set_lazy_variable(variable_name, argument_tokens.into_token_stream());
+
+        Ok(TokenStream::new())
+    }
+}
+
+pub(crate) struct GetCommand;
+
+impl CommandDefinition for GetCommand {
+    const COMMAND_NAME: &'static str = "get";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let variable_name = interpret_variable_name(interpreter, argument.tokens(), command_span)?;
+        match interpreter.get_variable(&variable_name, command_span)? {
+            Some(variable_value) => Ok(variable_value),
+            None => Err(command_span.error(format!("The variable #{} wasn't set.", variable_name))),
+        }
+    }
+}
+
+pub(crate) struct SetNamedCommand;
+
+impl CommandDefinition for SetNamedCommand {
+    const COMMAND_NAME: &'static str = "set_named";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut argument_tokens = argument.tokens();
+        let variable_name_tokens = match parse_until_punct(&mut argument_tokens, '=') {
+            Some(name_tokens) => Tokens::new(name_tokens),
+            None => {
+                return Err(command_span
+                    .error("A set_named call is expected to start with `name tokens = ..`"));
+            }
+        };
+        let variable_name =
+            interpret_variable_name(interpreter, variable_name_tokens, command_span)?;
+
+        let result_tokens = interpreter.interpret_tokens(argument_tokens)?;
+        interpreter.set_variable(variable_name, result_tokens);
+
+        Ok(TokenStream::new())
+    }
+}
+
+pub(crate) struct ExportCommand;
+
+impl CommandDefinition for ExportCommand {
+    const COMMAND_NAME: &'static str = "export";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        const EXPECTED: &str =
+            "An export call is expected to look like [!export! macro_name = #var1 #var2 ..]";
+        let mut argument_tokens = argument.tokens();
+        let macro_name = match argument_tokens.next_as_ident() {
+            Some(ident) => ident,
+            None => return Err(command_span.error(EXPECTED)),
+        };
+        if argument_tokens.next_as_punct_matching('=').is_none() {
+            return Err(command_span.error(EXPECTED));
+        }
+
+        // The variables are defined at the start of the exported preinterpret! call, using their
+        // current values. Eager values are wrapped in [!raw! ..] so they aren't interpreted again.
+        let mut definitions = TokenStream::new();
+        while argument_tokens.peek().is_some() {
+            let variable = match parse_variable(&mut argument_tokens) {
+                Some(variable) => variable,
+                None => return Err(command_span.error(EXPECTED)),
+            };
+            let (command_name, value) = match interpreter.find_variable(&variable.to_string()) {
+                Some(VariableValue::Eager(tokens)) => (
+                    SetCommand::COMMAND_NAME,
+                    command_tokens(RawCommand::COMMAND_NAME, tokens.clone()),
+                ),
+                Some(VariableValue::Lazy(uninterpreted_tokens)) => {
+                    (SetLazyCommand::COMMAND_NAME, uninterpreted_tokens.clone())
+                }
+                None => {
+                    return Err(variable
+                        .span()
+                        .error(format!("The variable #{} wasn't set.", variable)));
+                }
+            };
+            if contains_dollar(&value) {
+                return Err(variable.span().error(format!(
+                    "The variable #{} can't be exported, because its value contains a `$`, which can't be escaped inside a macro_rules! definition.",
+                    variable,
+                )));
+            }
+            let mut set_arguments = TokenStream::new();
+            set_arguments.extend([
+                TokenTree::Punct(Punct::new('#', Spacing::Alone)),
+                TokenTree::Ident(variable),
+                TokenTree::Punct(Punct::new('=', Spacing::Alone)),
+            ]);
+            set_arguments.extend(value);
+            definitions.extend(command_tokens(command_name, set_arguments));
+        }
+
+        // macro_rules! #macro_name {
+        //     ($($body:tt)*) => { ::preinterpret::preinterpret! { #definitions $($body)* } };
+        // }
+        let body_matcher = TokenStream::from_str("$($body:tt)*").unwrap();
+        let body_transcriber = TokenStream::from_str("$($body)*").unwrap();
+        let mut preinterpret_call = TokenStream::from_str("::preinterpret::preinterpret!").unwrap();
+        preinterpret_call.extend(iter::once(TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            definitions.into_iter().chain(body_transcriber).collect(),
+        ))));
+        let mut macro_rule = TokenStream::new();
+        macro_rule.extend([
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, body_matcher)),
+            TokenTree::Punct(Punct::new('=', Spacing::Joint)),
+            TokenTree::Punct(Punct::new('>', Spacing::Alone)),
+            TokenTree::Group(Group::new(Delimiter::Brace, preinterpret_call)),
+            TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+        ]);
+        let mut output = TokenStream::new();
+        output.extend([
+            TokenTree::Ident(Ident::new("macro_rules", command_span)),
+            TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+            TokenTree::Ident(macro_name),
+            TokenTree::Group(Group::new(Delimiter::Brace, macro_rule)),
+        ]);
+        Ok(output)
+    }
+}
+
+/// Outputs the tokens of the command invocation `[!command_name! arguments]`
+fn command_tokens(command_name: &str, arguments: TokenStream) -> TokenStream {
+    let mut command = TokenStream::new();
+    command.extend([
+        TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+        TokenTree::Ident(Ident::new(command_name, Span::call_site())),
+        TokenTree::Punct(Punct::new('!', Spacing::Alone)),
+    ]);
+    command.extend(arguments);
+    TokenStream::from(TokenTree::Group(Group::new(Delimiter::Bracket, command)))
+}
+
+fn contains_dollar(tokens: &TokenStream) -> bool {
+    tokens
+        .clone()
+        .into_iter()
+        .any(|token_tree| match token_tree {
+            TokenTree::Punct(punct) => punct.as_char() == '$',
+            TokenTree::Group(group) => contains_dollar(&group.stream()),
+            _ => false,
+        })
+}
+
+/// Interprets the tokens and concatenates them into a variable name, which must be a valid ident.
+fn interpret_variable_name(
+    interpreter: &mut Interpreter,
+    name_tokens: Tokens,
+    command_span: Span,
+) -> Result<String> {
+    let interpreted = interpreter.interpret_tokens(name_tokens)?;
+    let variable_name = concat_recursive(interpreted);
+    if parse_str::<Ident>(&variable_name).is_err() {
+        return Err(command_span.error(format!(
+            "`{}` is not a valid variable name. It is expected to concatenate into a valid ident.",
+            variable_name
+        )));
+    }
+    Ok(variable_name)
+}
+
 pub(crate) struct RawCommand;
 
 impl CommandDefinition for RawCommand {
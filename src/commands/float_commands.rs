@@ -0,0 +1,420 @@
+use crate::internal_prelude::*;
+
+//========
+// Helpers
+//========
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FloatKind {
+    F32,
+    F64,
+}
+
+impl FloatKind {
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "f32" => Some(Self::F32),
+            "f64" => Some(Self::F64),
+            _ => None,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+        }
+    }
+}
+
+/// A float, which is calculated with as an `f64`, and which remembers any suffix. Values of type
+/// `f32` are rounded to `f32` precision after each operation.
+#[derive(Clone, Copy)]
+struct FloatValue {
+    value: f64,
+    kind: Option<FloatKind>,
+}
+
+impl FloatValue {
+    /// Parses a float or integer literal, such as `1.5`, `2f32` or `3`. An integer literal with an
+    /// integer suffix such as `3u8` isn't accepted.
+    fn parse(literal: &Literal) -> Result<Option<Self>> {
+        let (value, suffix) = match Lit::new(literal.clone()) {
+            Lit::Float(lit_float) => (
+                lit_float.base10_parse::<f64>()?,
+                lit_float.suffix().to_string(),
+            ),
+            Lit::Int(lit_int) if matches!(lit_int.suffix(), "" | "f32" | "f64") => {
+                (lit_int.base10_parse::<f64>()?, lit_int.suffix().to_string())
+            }
+            _ => return Ok(None),
+        };
+        let kind = match suffix.as_str() {
+            "" => None,
+            suffix => FloatKind::from_suffix(suffix),
+        };
+        Self::checked(value, kind, literal.span())
+            .map(Some)
+            .map_err(|_| {
+                literal
+                    .span()
+                    .error(format!("{} is out of range for a float", literal))
+            })
+    }
+
+    /// Creates a float from the result of an operation, rounding it to the precision of its kind,
+    /// and erroring if it is NaN or infinite, which can't be represented as a literal.
+    fn checked(value: f64, kind: Option<FloatKind>, span: Span) -> Result<Self> {
+        let value = match kind {
+            Some(FloatKind::F32) => value as f32 as f64,
+            _ => value,
+        };
+        if value.is_nan() {
+            return Err(span.error("The result is NaN, which can't be output as a literal"));
+        }
+        if value.is_infinite() {
+            let type_name = kind.map_or("f64", FloatKind::suffix);
+            return Err(span.error(format!(
+                "The result is infinite, or too large for an {}, so can't be output as a literal",
+                type_name
+            )));
+        }
+        Ok(Self { value, kind })
+    }
+
+    /// Combines the kinds of two floats, erroring if they have different suffixes.
+    fn combine_kinds(self, other: Self, span: Span) -> Result<Option<FloatKind>> {
+        match (self.kind, other.kind) {
+            (Some(left), Some(right)) if left != right => Err(span.error(format!(
+                "Cannot combine floats of different types `{}` and `{}`",
+                left.suffix(),
+                right.suffix(),
+            ))),
+            (Some(kind), _) | (_, Some(kind)) => Ok(Some(kind)),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Formats the absolute value of the float to the given number of decimal places, or else
+    /// with the shortest representation which round-trips to the same value. Also returns whether
+    /// the formatted value is negative, which excludes values which format as zero, such as `-0.0`.
+    fn format_digits(self, precision: Option<usize>) -> (bool, String) {
+        let digits = match (self.kind, precision) {
+            (Some(FloatKind::F32), Some(precision)) => {
+                format!("{:.*}", precision, (self.value as f32).abs())
+            }
+            (_, Some(precision)) => format!("{:.*}", precision, self.value.abs()),
+            (Some(FloatKind::F32), None) => format!("{:?}", (self.value as f32).abs()),
+            (_, None) => format!("{:?}", self.value.abs()),
+        };
+        let is_zero = digits.chars().all(|c| c == '0' || c == '.');
+        (self.value.is_sign_negative() && !is_zero, digits)
+    }
+
+    fn into_token_stream(self, span: Span) -> Result<TokenStream> {
+        self.into_literal_with_precision(None, span)
+    }
+
+    /// Outputs the float, with a `-` punct before it if it is negative, as a literal can't be
+    /// negative.
+    fn into_literal_with_precision(
+        self,
+        precision: Option<usize>,
+        span: Span,
+    ) -> Result<TokenStream> {
+        let (is_negative, mut digits) = self.format_digits(precision);
+        let mut output = TokenStream::new();
+        if is_negative {
+            let mut minus = Punct::new('-', Spacing::Alone);
+            minus.set_span(span);
+            output.extend(iter::once(TokenTree::Punct(minus)));
+        }
+        if !digits.contains(['.', 'e', 'E']) {
+            digits.push_str(".0");
+        }
+        if let Some(kind) = self.kind {
+            digits.push_str(kind.suffix());
+        }
+        output.extend(iter::once(TokenTree::Literal(parse_float_literal(
+            &digits, span,
+        )?)));
+        Ok(output)
+    }
+
+    /// Outputs the float as a string literal such as `"-1.5"`, without any suffix.
+    fn into_string_with_precision(self, precision: Option<usize>, span: Span) -> TokenStream {
+        let (is_negative, digits) = self.format_digits(precision);
+        let sign = if is_negative { "-" } else { "" };
+        let mut literal = Literal::string(&format!("{}{}", sign, digits));
+        literal.set_span(span);
+        TokenTree::Literal(literal).into()
+    }
+}
+
+fn parse_float_literal(digits: &str, span: Span) -> Result<Literal> {
+    let mut literal = Literal::from_str(digits)
+        .map_err(|_| span.error(format!("`{}` is not a valid float literal", digits)))?;
+    literal.set_span(span);
+    Ok(literal)
+}
+
+/// Parses a list of floats, each of which is a float or integer literal, optionally negated with
+/// a `-` (as a negative number is two token trees).
+fn parse_floats(stream: TokenStream, span: Span) -> Result<Vec<FloatValue>> {
+    let mut floats = Vec::new();
+    let mut tokens = Tokens::new(flatten_transparent_groups(stream));
+    while let Some(token_tree) = tokens.next() {
+        let (negative, token_tree) = match token_tree {
+            TokenTree::Punct(punct) if punct.as_char() == '-' => match tokens.next() {
+                Some(token_tree) => (true, token_tree),
+                None => return Err(punct.span().error("Expected a float after the `-`")),
+            },
+            token_tree => (false, token_tree),
+        };
+        let float = match &token_tree {
+            TokenTree::Literal(literal) => FloatValue::parse(literal)?,
+            _ => None,
+        };
+        match float {
+            Some(mut float) => {
+                if negative {
+                    float.value = -float.value;
+                }
+                floats.push(float);
+            }
+            None => {
+                return Err(token_tree.span().error(format!(
+                    "Expected a float literal such as `1.5` or `2f32`, but found `{}`",
+                    token_tree
+                )))
+            }
+        }
+    }
+    if floats.is_empty() {
+        return Err(span.error("Expected at least one float, but the input was empty"));
+    }
+    Ok(floats)
+}
+
+fn parse_single_float(stream: TokenStream, span: Span) -> Result<FloatValue> {
+    match parse_floats(stream, span)?[..] {
+        [float] => Ok(float),
+        ref floats => Err(span.error(format!(
+            "Expected exactly one float, but found {}",
+            floats.len()
+        ))),
+    }
+}
+
+/// Combines the floats in turn with an operation.
+fn execute_float_fold(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    operation: fn(f64, f64) -> f64,
+) -> Result<TokenStream> {
+    let floats = parse_floats(argument.interpret(interpreter)?, command_span)?;
+    let mut result = floats[0];
+    for float in &floats[1..] {
+        let kind = result.combine_kinds(*float, command_span)?;
+        result = FloatValue::checked(operation(result.value, float.value), kind, command_span)?;
+    }
+    result.into_token_stream(command_span)
+}
+
+/// Applies an operation to two floats.
+fn execute_float_binary(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    operation: fn(f64, f64) -> f64,
+) -> Result<TokenStream> {
+    let floats = parse_floats(argument.interpret(interpreter)?, command_span)?;
+    let (left, right) = match floats[..] {
+        [left, right] => (left, right),
+        _ => {
+            return Err(command_span.error(format!(
+                "Expected exactly two floats, but found {}",
+                floats.len()
+            )))
+        }
+    };
+    let kind = left.combine_kinds(right, command_span)?;
+    FloatValue::checked(operation(left.value, right.value), kind, command_span)?
+        .into_token_stream(command_span)
+}
+
+/// Applies an operation to a single float.
+fn execute_float_unary(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    operation: fn(f64) -> f64,
+) -> Result<TokenStream> {
+    let float = parse_single_float(argument.interpret(interpreter)?, command_span)?;
+    FloatValue::checked(operation(float.value), float.kind, command_span)?
+        .into_token_stream(command_span)
+}
+
+//===========================
+// Float arithmetic commands
+//===========================
+
+pub(crate) struct FloatAddCommand;
+
+impl CommandDefinition for FloatAddCommand {
+    const COMMAND_NAME: &'static str = "float_add";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_fold(interpreter, argument, command_span, |l, r| l + r)
+    }
+}
+
+pub(crate) struct FloatSubCommand;
+
+impl CommandDefinition for FloatSubCommand {
+    const COMMAND_NAME: &'static str = "float_sub";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_binary(interpreter, argument, command_span, |l, r| l - r)
+    }
+}
+
+pub(crate) struct FloatMulCommand;
+
+impl CommandDefinition for FloatMulCommand {
+    const COMMAND_NAME: &'static str = "float_mul";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_fold(interpreter, argument, command_span, |l, r| l * r)
+    }
+}
+
+pub(crate) struct FloatDivCommand;
+
+impl CommandDefinition for FloatDivCommand {
+    const COMMAND_NAME: &'static str = "float_div";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_binary(interpreter, argument, command_span, |l, r| l / r)
+    }
+}
+
+pub(crate) struct FloatSqrtCommand;
+
+impl CommandDefinition for FloatSqrtCommand {
+    const COMMAND_NAME: &'static str = "float_sqrt";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_unary(interpreter, argument, command_span, f64::sqrt)
+    }
+}
+
+pub(crate) struct FloatFloorCommand;
+
+impl CommandDefinition for FloatFloorCommand {
+    const COMMAND_NAME: &'static str = "float_floor";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_unary(interpreter, argument, command_span, f64::floor)
+    }
+}
+
+pub(crate) struct FloatCeilCommand;
+
+impl CommandDefinition for FloatCeilCommand {
+    const COMMAND_NAME: &'static str = "float_ceil";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_unary(interpreter, argument, command_span, f64::ceil)
+    }
+}
+
+pub(crate) struct FloatRoundCommand;
+
+impl CommandDefinition for FloatRoundCommand {
+    const COMMAND_NAME: &'static str = "float_round";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_float_unary(interpreter, argument, command_span, f64::round)
+    }
+}
+
+//==========================
+// Float formatting command
+//==========================
+
+pub(crate) struct FloatFormatCommand;
+
+impl CommandDefinition for FloatFormatCommand {
+    const COMMAND_NAME: &'static str = "float_format";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        const EXPECTED: &str =
+            "Expected a float followed by options separated by commas: `precision = N` or `string`";
+        let separator = TokenTree::Punct(Punct::new(',', Spacing::Alone));
+        let mut segments =
+            split_top_level(argument.interpret(interpreter)?, &separator).into_iter();
+        let float = parse_single_float(segments.next().unwrap_or_default(), command_span)?;
+        let mut precision = None;
+        let mut string = false;
+        for segment in segments {
+            let mut tokens = Tokens::new(segment);
+            match tokens.next_as_ident() {
+                Some(option) if option == "string" => string = true,
+                Some(option) if option == "precision" => {
+                    if tokens.next_as_punct_matching('=').is_none() {
+                        return Err(option.span().error(EXPECTED));
+                    }
+                    precision = Some(parse_usize(tokens.next(), option.span())?);
+                }
+                _ => return Err(command_span.error(EXPECTED)),
+            }
+            if let Some(token_tree) = tokens.next() {
+                return Err(token_tree.span().error(EXPECTED));
+            }
+        }
+
+        if string {
+            Ok(float.into_string_with_precision(precision, command_span))
+        } else {
+            float.into_literal_with_precision(precision, command_span)
+        }
+    }
+}
@@ -0,0 +1,668 @@
+use crate::internal_prelude::*;
+
+//========
+// Helpers
+//========
+
+/// Evaluates the interpreted tokens as an expression, which must result in an integer.
+fn evaluate_integer(tokens: TokenStream, span: Span) -> Result<IntegerValue> {
+    if tokens.is_empty() {
+        return Err(span.error("Expected an integer, but found nothing"));
+    }
+    evaluate_expression(tokens, span)?.expect_integer(span)
+}
+
+/// Parses a list of integers, each of which is an integer literal (optionally negated with a `-`,
+/// as a negative number is two token trees), or a group which is evaluated as an expression.
+fn parse_integers(stream: TokenStream, span: Span) -> Result<Vec<IntegerValue>> {
+    let mut integers = Vec::new();
+    let mut tokens = Tokens::new(stream);
+    while let Some(token_tree) = tokens.next() {
+        let integer = match &token_tree {
+            TokenTree::Punct(punct) if punct.as_char() == '-' => {
+                let operand = tokens.next().ok_or_else(|| {
+                    punct.span().error("Expected an integer after the `-`")
+                })?;
+                let mut negation: TokenStream = TokenTree::Punct(punct.clone()).into();
+                negation.extend(iter::once(operand));
+                evaluate_integer(negation, punct.span())?
+            }
+            TokenTree::Literal(_) | TokenTree::Group(_) => {
+                evaluate_integer(token_tree.clone().into(), token_tree.span())?
+            }
+            _ => {
+                return Err(token_tree.span().error(format!(
+                    "Expected an integer literal or a (..) group containing an expression, but found `{}`",
+                    token_tree
+                )))
+            }
+        };
+        integers.push(integer);
+    }
+    if integers.is_empty() {
+        return Err(span.error("Expected at least one integer, but the input was empty"));
+    }
+    Ok(integers)
+}
+
+/// Parses exactly two integers, such as for `[!shl! 1u32 4]`.
+fn parse_two_integers(stream: TokenStream, span: Span) -> Result<(IntegerValue, IntegerValue)> {
+    match parse_integers(stream, span)?[..] {
+        [left, right] => Ok((left, right)),
+        ref integers => Err(span.error(format!(
+            "Expected exactly two integers, but found {}",
+            integers.len()
+        ))),
+    }
+}
+
+/// Returns the suffix shared by all the integers, if there is one.
+fn shared_kind(integers: &[IntegerValue]) -> Option<IntegerKind> {
+    let kind = integers[0].kind;
+    if integers.iter().all(|integer| integer.kind == kind) {
+        kind
+    } else {
+        None
+    }
+}
+
+fn integer_output(integer: IntegerValue, span: Span) -> Result<TokenStream> {
+    Ok(TokenTree::Literal(integer.to_literal(span)?).into())
+}
+
+/// Returns the char if the tokens are a single char literal such as `'a'`.
+fn parse_char(tokens: &TokenStream) -> Option<char> {
+    let mut tokens = tokens.clone().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(literal)), None) => match Lit::new(literal) {
+            Lit::Char(lit_char) => Some(lit_char.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+//================
+// Range commands
+//================
+
+pub(crate) struct RangeCommand;
+
+impl CommandDefinition for RangeCommand {
+    const COMMAND_NAME: &'static str = "range";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let range = parse_range(&mut tokens, &["step"], command_span)?;
+        let step = match tokens.next() {
+            Some(_) => {
+                let step =
+                    evaluate_integer(iter::from_fn(|| tokens.next()).collect(), command_span)?;
+                if step.value <= 0 {
+                    return Err(command_span.error(format!(
+                        "The step must be positive, but was {}. The direction of the range is determined by its bounds.",
+                        step
+                    )));
+                }
+                Some(step)
+            }
+            None => None,
+        };
+        if range.start.is_empty() || range.end.is_empty() {
+            return Err(command_span.error("A range requires both a start and an end bound"));
+        }
+
+        if let (Some(start), Some(end)) = (parse_char(&range.start), parse_char(&range.end)) {
+            if step.map_or(false, |step| step.kind.is_some()) {
+                return Err(command_span.error("The step of a char range must be unsuffixed"));
+            }
+            let step = step.map_or(1, |step| step.value);
+            let code_points = range_values(
+                start as i128,
+                end as i128,
+                step,
+                range.inclusive,
+                command_span,
+            )?;
+            // The surrogate code points between U+D800 and U+DFFF aren't chars, so are skipped
+            return Ok(code_points
+                .filter_map(|code_point| char::from_u32(code_point as u32))
+                .map(|char| {
+                    let mut literal = Literal::character(char);
+                    literal.set_span(command_span);
+                    TokenTree::Literal(literal)
+                })
+                .collect());
+        }
+
+        let start = evaluate_integer(range.start, command_span)?;
+        let end = evaluate_integer(range.end, command_span)?;
+        let kind = start.combine_kinds(end, command_span)?;
+        let kind = IntegerKind::combine(kind, step.and_then(|step| step.kind), command_span)?;
+        let step = step.map_or(1, |step| step.value);
+        range_values(start.value, end.value, step, range.inclusive, command_span)?
+            .map(|value| {
+                Ok(TokenTree::Literal(
+                    IntegerValue { value, kind }.to_literal(command_span)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Iterates from `start` towards `end` in increments of `step`, counting downwards if `end` is
+/// less than `start`. Errors if the range would output more than [`MAX_GENERATED_LENGTH`] values.
+fn range_values(
+    start: i128,
+    end: i128,
+    step: i128,
+    inclusive: bool,
+    span: Span,
+) -> Result<impl Iterator<Item = i128>> {
+    let distance = start.abs_diff(end);
+    let step_size = step as u128;
+    let length = if inclusive {
+        distance / step_size + 1
+    } else {
+        (distance / step_size) + u128::from(distance % step_size != 0)
+    };
+    if length > MAX_GENERATED_LENGTH {
+        return Err(span.error(format!(
+            "The range has {} values, but at most {} are supported",
+            length, MAX_GENERATED_LENGTH
+        )));
+    }
+    let ascending = start <= end;
+    let mut next = Some(start);
+    Ok(iter::from_fn(move || {
+        let current = next?;
+        let in_range = match (ascending, inclusive) {
+            (true, true) => current <= end,
+            (true, false) => current < end,
+            (false, true) => current >= end,
+            (false, false) => current > end,
+        };
+        if !in_range {
+            return None;
+        }
+        next = if ascending {
+            current.checked_add(step)
+        } else {
+            current.checked_sub(step)
+        };
+        Some(current)
+    }))
+}
+
+//================
+// Radix commands
+//================
+
+/// The options after the value, such as `[!hex! #value, pad = 4, group = 2, string]`.
+struct RadixOptions {
+    pad: usize,
+    group: Option<usize>,
+    string: bool,
+}
+
+impl RadixOptions {
+    fn parse(segments: impl Iterator<Item = TokenStream>, span: Span) -> Result<Self> {
+        let mut options = Self {
+            pad: 0,
+            group: None,
+            string: false,
+        };
+        for segment in segments {
+            let mut tokens = Tokens::new(segment);
+            let option = match tokens.next_as_ident() {
+                Some(option) => option,
+                None => return Err(span.error(Self::EXPECTED)),
+            };
+            if option == "string" {
+                options.string = true;
+            } else if option == "pad" || option == "group" {
+                if tokens.next_as_punct_matching('=').is_none() {
+                    return Err(option.span().error(Self::EXPECTED));
+                }
+                let value = parse_usize(tokens.next(), option.span())?;
+                if option == "pad" {
+                    options.pad = value;
+                } else if value == 0 {
+                    return Err(option.span().error("The group size must be greater than 0"));
+                } else {
+                    options.group = Some(value);
+                }
+            } else {
+                return Err(option.span().error(Self::EXPECTED));
+            }
+            if let Some(token_tree) = tokens.next() {
+                return Err(token_tree.span().error(Self::EXPECTED));
+            }
+        }
+        Ok(options)
+    }
+
+    const EXPECTED: &'static str =
+        "Expected options separated by commas: `pad = N`, `group = N` or `string`";
+}
+
+fn execute_radix(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    prefix: &str,
+    format_digits: fn(u128) -> String,
+) -> Result<TokenStream> {
+    let separator = TokenTree::Punct(Punct::new(',', Spacing::Alone));
+    let mut segments = split_top_level(argument.interpret(interpreter)?, &separator).into_iter();
+    let value = evaluate_integer(segments.next().unwrap_or_default(), command_span)?;
+    let options = RadixOptions::parse(segments, command_span)?;
+    if value.value < 0 {
+        return Err(command_span.error(format!(
+            "Only non-negative integers can be formatted, but found {}",
+            value
+        )));
+    }
+    // Check the value is valid for its suffix
+    value.to_literal(command_span)?;
+
+    let mut digits = format_digits(value.value as u128);
+    while digits.len() < options.pad {
+        digits.insert(0, '0');
+    }
+    if let Some(group) = options.group {
+        let mut grouped = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % group == 0 {
+                grouped.push('_');
+            }
+            grouped.push(digit);
+        }
+        digits = grouped;
+    }
+
+    let mut output = format!("{}{}", prefix, digits);
+    if options.string {
+        let mut literal = Literal::string(&output);
+        literal.set_span(command_span);
+        return Ok(TokenTree::Literal(literal).into());
+    }
+    if let Some(kind) = value.kind {
+        output.push_str(kind.suffix());
+    }
+    let mut literal = Literal::from_str(&output)
+        .map_err(|_| command_span.error(format!("`{}` is not a valid literal", output)))?;
+    literal.set_span(command_span);
+    Ok(TokenTree::Literal(literal).into())
+}
+
+pub(crate) struct HexCommand;
+
+impl CommandDefinition for HexCommand {
+    const COMMAND_NAME: &'static str = "hex";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_radix(interpreter, argument, command_span, "0x", |value| {
+            format!("{:X}", value)
+        })
+    }
+}
+
+pub(crate) struct BinCommand;
+
+impl CommandDefinition for BinCommand {
+    const COMMAND_NAME: &'static str = "bin";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_radix(interpreter, argument, command_span, "0b", |value| {
+            format!("{:b}", value)
+        })
+    }
+}
+
+pub(crate) struct OctCommand;
+
+impl CommandDefinition for OctCommand {
+    const COMMAND_NAME: &'static str = "oct";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_radix(interpreter, argument, command_span, "0o", |value| {
+            format!("{:o}", value)
+        })
+    }
+}
+
+//==================
+// Bitwise commands
+//==================
+
+/// Combines the integers in turn with a bitwise operation, whose result always fits in the type.
+fn execute_bitwise_fold(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    operation: fn(i128, i128) -> i128,
+) -> Result<TokenStream> {
+    let integers = parse_integers(argument.interpret(interpreter)?, command_span)?;
+    let mut result = integers[0];
+    for integer in &integers[1..] {
+        let kind = result.combine_kinds(*integer, command_span)?;
+        result.check_fits(kind, command_span)?;
+        integer.check_fits(kind, command_span)?;
+        result = IntegerValue {
+            value: operation(result.value, integer.value),
+            kind,
+        };
+    }
+    integer_output(result, command_span)
+}
+
+pub(crate) struct BitAndCommand;
+
+impl CommandDefinition for BitAndCommand {
+    const COMMAND_NAME: &'static str = "bitand";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_bitwise_fold(interpreter, argument, command_span, |l, r| l & r)
+    }
+}
+
+pub(crate) struct BitOrCommand;
+
+impl CommandDefinition for BitOrCommand {
+    const COMMAND_NAME: &'static str = "bitor";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_bitwise_fold(interpreter, argument, command_span, |l, r| l | r)
+    }
+}
+
+pub(crate) struct XorCommand;
+
+impl CommandDefinition for XorCommand {
+    const COMMAND_NAME: &'static str = "xor";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_bitwise_fold(interpreter, argument, command_span, |l, r| l ^ r)
+    }
+}
+
+pub(crate) struct ShlCommand;
+
+impl CommandDefinition for ShlCommand {
+    const COMMAND_NAME: &'static str = "shl";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (value, amount) = parse_two_integers(argument.interpret(interpreter)?, command_span)?;
+        integer_output(value.shl(amount, command_span)?, command_span)
+    }
+}
+
+pub(crate) struct ShrCommand;
+
+impl CommandDefinition for ShrCommand {
+    const COMMAND_NAME: &'static str = "shr";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (value, amount) = parse_two_integers(argument.interpret(interpreter)?, command_span)?;
+        integer_output(value.shr(amount, command_span)?, command_span)
+    }
+}
+
+pub(crate) struct PowCommand;
+
+impl CommandDefinition for PowCommand {
+    const COMMAND_NAME: &'static str = "pow";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (base, exponent) = parse_two_integers(argument.interpret(interpreter)?, command_span)?;
+        integer_output(base.pow(exponent, command_span)?, command_span)
+    }
+}
+
+pub(crate) struct BitMaskCommand;
+
+impl CommandDefinition for BitMaskCommand {
+    const COMMAND_NAME: &'static str = "bit_mask";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let range = parse_range(&mut tokens, &[], command_span)?;
+        if range.start.is_empty() || range.end.is_empty() {
+            return Err(command_span.error("A bit mask requires a range such as `4..8`"));
+        }
+        let low = evaluate_integer(range.start, command_span)?;
+        let high = evaluate_integer(range.end, command_span)?;
+        let kind = low.combine_kinds(high, command_span)?;
+        let bits = kind.map_or(128, IntegerKind::bits) as i128;
+        let high_value = if range.inclusive {
+            high.value + 1
+        } else {
+            high.value
+        };
+        if low.value < 0 || high_value < low.value || high_value > bits {
+            return Err(command_span.error(format!(
+                "The bit range must be within 0..{}, with the start no greater than the end",
+                bits
+            )));
+        }
+        let width = (high_value - low.value) as u32;
+        let mask = match width {
+            0 => 0,
+            128 => u128::MAX,
+            width => ((1u128 << width) - 1) << low.value,
+        };
+        let mask = match kind {
+            // Like `shl`, a mask including the sign bit of a signed type is negative
+            Some(kind) if kind.is_signed() => {
+                let unused_bits = 128 - kind.bits();
+                Some(((mask << unused_bits) as i128) >> unused_bits)
+            }
+            _ => i128::try_from(mask).ok(),
+        };
+        let mask = IntegerValue::checked(mask, kind, "bit mask", command_span)?;
+        integer_output(mask, command_span)
+    }
+}
+
+//====================
+// Aggregate commands
+//====================
+
+/// Combines the integers in turn with a checked operation, computing in `i128` space. The result
+/// is unsuffixed unless all the integers share a suffix.
+fn execute_aggregate(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    operation_name: &str,
+    operation: fn(i128, i128) -> Option<i128>,
+) -> Result<TokenStream> {
+    let integers = parse_integers(argument.interpret(interpreter)?, command_span)?;
+    let mut result = Some(integers[0].value);
+    for integer in &integers[1..] {
+        result = result.and_then(|result| operation(result, integer.value));
+    }
+    let result =
+        IntegerValue::checked(result, shared_kind(&integers), operation_name, command_span)?;
+    integer_output(result, command_span)
+}
+
+pub(crate) struct MinCommand;
+
+impl CommandDefinition for MinCommand {
+    const COMMAND_NAME: &'static str = "min";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_aggregate(interpreter, argument, command_span, "min", |l, r| {
+            Some(l.min(r))
+        })
+    }
+}
+
+pub(crate) struct MaxCommand;
+
+impl CommandDefinition for MaxCommand {
+    const COMMAND_NAME: &'static str = "max";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_aggregate(interpreter, argument, command_span, "max", |l, r| {
+            Some(l.max(r))
+        })
+    }
+}
+
+pub(crate) struct SumCommand;
+
+impl CommandDefinition for SumCommand {
+    const COMMAND_NAME: &'static str = "sum";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_aggregate(
+            interpreter,
+            argument,
+            command_span,
+            "sum",
+            i128::checked_add,
+        )
+    }
+}
+
+pub(crate) struct ProductCommand;
+
+impl CommandDefinition for ProductCommand {
+    const COMMAND_NAME: &'static str = "product";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_aggregate(
+            interpreter,
+            argument,
+            command_span,
+            "product",
+            i128::checked_mul,
+        )
+    }
+}
+
+pub(crate) struct AbsCommand;
+
+impl CommandDefinition for AbsCommand {
+    const COMMAND_NAME: &'static str = "abs";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let integer = match parse_integers(argument.interpret(interpreter)?, command_span)?[..] {
+            [integer] => integer,
+            ref integers => {
+                return Err(command_span.error(format!(
+                    "Expected exactly one integer, but found {}",
+                    integers.len()
+                )))
+            }
+        };
+        let result = IntegerValue::checked(
+            integer.value.checked_abs(),
+            integer.kind,
+            "abs",
+            command_span,
+        )?;
+        integer_output(result, command_span)
+    }
+}
+
+pub(crate) struct ClampCommand;
+
+impl CommandDefinition for ClampCommand {
+    const COMMAND_NAME: &'static str = "clamp";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let integers = parse_integers(argument.interpret(interpreter)?, command_span)?;
+        let (value, min, max) = match integers[..] {
+            [value, min, max] => (value, min, max),
+            _ => {
+                return Err(command_span.error(format!(
+                    "Expected a value, a minimum and a maximum, but found {} integers",
+                    integers.len()
+                )))
+            }
+        };
+        if min.value > max.value {
+            return Err(command_span.error(format!(
+                "The minimum {} is greater than the maximum {}",
+                min, max
+            )));
+        }
+        let result = IntegerValue {
+            value: value.value.clamp(min.value, max.value),
+            kind: shared_kind(&integers),
+        };
+        integer_output(result, command_span)
+    }
+}
@@ -0,0 +1,665 @@
+use crate::internal_prelude::*;
+
+//========
+// Helpers
+//========
+
+/// Parses the remaining tokens as lists, each of which is a group such as `[a b c]`, whose items
+/// are the top-level token trees inside it.
+fn parse_lists(mut tokens: Tokens, span: Span) -> Result<Vec<Vec<TokenTree>>> {
+    let mut lists = Vec::new();
+    while let Some(token_tree) = tokens.next() {
+        match token_tree {
+            TokenTree::Group(group) => lists.push(group.stream().into_iter().collect()),
+            token_tree => {
+                return Err(token_tree.span().error(format!(
+                    "Expected a list wrapped in a group such as [a b c], but found `{}`",
+                    token_tree
+                )))
+            }
+        }
+    }
+    if lists.is_empty() {
+        return Err(span.error("Expected at least one list, such as [a b c]"));
+    }
+    Ok(lists)
+}
+
+/// Interprets the tokens as a single list group, such as `[a b c]` or `[#items]`, and returns its
+/// items.
+fn interpret_list(
+    interpreter: &mut Interpreter,
+    list_tokens: TokenStream,
+    span: Span,
+) -> Result<Vec<TokenTree>> {
+    let interpreted = interpreter.interpret_tokens(Tokens::new(list_tokens))?;
+    let mut lists = parse_lists(Tokens::new(interpreted), span)?;
+    if lists.len() != 1 {
+        return Err(span.error("Expected a single list wrapped in a group such as [a b c]"));
+    }
+    Ok(lists.remove(0))
+}
+
+/// Consumes raw tokens up to and including the terminator, returning the tokens before it.
+/// The terminator is either a `=>` or a keyword ident.
+fn parse_until_terminator(
+    tokens: &mut Tokens,
+    terminator: &str,
+    span: Span,
+) -> Result<TokenStream> {
+    let mut preceding = TokenStream::new();
+    loop {
+        let token_tree = match tokens.next() {
+            Some(token_tree) => token_tree,
+            None => return Err(span.error(format!("Expected `{}`", terminator))),
+        };
+        match &token_tree {
+            TokenTree::Ident(ident) if ident == terminator => return Ok(preceding),
+            TokenTree::Punct(punct)
+                if terminator == "=>"
+                    && punct.as_char() == '='
+                    && punct.spacing() == Spacing::Joint =>
+            {
+                if let Some(TokenTree::Punct(next)) = tokens.peek() {
+                    if next.as_char() == '>' {
+                        tokens.next();
+                        return Ok(preceding);
+                    }
+                }
+            }
+            _ => {}
+        }
+        preceding.extend(iter::once(token_tree));
+    }
+}
+
+/// Parses `#x in [..] <terminator>`, returning the variable name and the interpreted list items.
+fn parse_variable_in_list(
+    interpreter: &mut Interpreter,
+    tokens: &mut Tokens,
+    terminator: &str,
+    span: Span,
+) -> Result<(String, Vec<TokenTree>)> {
+    let variable = match parse_variable(tokens) {
+        Some(variable) => variable.to_string(),
+        None => return Err(span.error("Expected a variable such as `#x`")),
+    };
+    parse_keyword(tokens, "in", span)?;
+    let list_tokens = parse_until_terminator(tokens, terminator, span)?;
+    let items = interpret_list(interpreter, list_tokens, span)?;
+    Ok((variable, items))
+}
+
+/// Parses the final `{ .. }` body, which is left uninterpreted.
+fn parse_body(tokens: &mut Tokens, span: Span) -> Result<TokenStream> {
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Brace => {
+            Ok(group.stream())
+        }
+        _ => Err(span.error("Expected a body wrapped in { .. } at the end")),
+    }
+}
+
+fn parenthesized(items: impl IntoIterator<Item = TokenTree>, span: Span) -> TokenTree {
+    let mut group = Group::new(Delimiter::Parenthesis, items.into_iter().collect());
+    group.set_span(span);
+    TokenTree::Group(group)
+}
+
+//=====================
+// Combinator commands
+//=====================
+
+pub(crate) struct ZipCommand;
+
+impl CommandDefinition for ZipCommand {
+    const COMMAND_NAME: &'static str = "zip";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        let lists = parse_lists(tokens, command_span)?;
+        let length = lists[0].len();
+        if lists.iter().any(|list| list.len() != length) {
+            let lengths: Vec<_> = lists.iter().map(|list| list.len().to_string()).collect();
+            return Err(command_span.error(format!(
+                "All lists must have the same length to be zipped, but they have lengths {}",
+                lengths.join(", "),
+            )));
+        }
+        Ok((0..length)
+            .map(|index| parenthesized(lists.iter().map(|list| list[index].clone()), command_span))
+            .collect())
+    }
+}
+
+pub(crate) struct CartesianCommand;
+
+impl CommandDefinition for CartesianCommand {
+    const COMMAND_NAME: &'static str = "cartesian";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        let lists = parse_lists(tokens, command_span)?;
+        let length = lists.iter().fold(1u128, |length, list| {
+            length.saturating_mul(list.len() as u128)
+        });
+        if length > MAX_GENERATED_LENGTH {
+            return Err(command_span.error(format!(
+                "The cartesian product has {} items, but at most {} are supported",
+                length, MAX_GENERATED_LENGTH
+            )));
+        }
+        // Build up the combinations one list at a time, so the first list varies slowest
+        let mut combinations: Vec<Vec<TokenTree>> = vec![Vec::new()];
+        for list in lists {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    list.iter().map(move |item| {
+                        let mut combination = combination.clone();
+                        combination.push(item.clone());
+                        combination
+                    })
+                })
+                .collect();
+        }
+        Ok(combinations
+            .into_iter()
+            .map(|combination| parenthesized(combination, command_span))
+            .collect())
+    }
+}
+
+//=====================
+// Functional commands
+//=====================
+
+pub(crate) struct MapCommand;
+
+impl CommandDefinition for MapCommand {
+    const COMMAND_NAME: &'static str = "map";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = argument.tokens();
+        let (variable, items) =
+            parse_variable_in_list(interpreter, &mut tokens, "=>", command_span)?;
+        let body = parse_body(&mut tokens, command_span)?;
+        let mut output = TokenStream::new();
+        for item in items {
+            output.extend(interpreter.interpret_tokens_in_scope(
+                iter::once((variable.clone(), TokenStream::from(item))),
+                Tokens::new(body.clone()),
+            )?);
+        }
+        Ok(output)
+    }
+}
+
+pub(crate) struct FilterCommand;
+
+impl CommandDefinition for FilterCommand {
+    const COMMAND_NAME: &'static str = "filter";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = argument.tokens();
+        let (variable, items) =
+            parse_variable_in_list(interpreter, &mut tokens, "if", command_span)?;
+        let condition = parse_body(&mut tokens, command_span)?;
+        let mut output = TokenStream::new();
+        for item in items {
+            let interpreted_condition = interpreter.interpret_tokens_in_scope(
+                iter::once((variable.clone(), TokenStream::from(item.clone()))),
+                Tokens::new(condition.clone()),
+            )?;
+            match evaluate_expression(interpreted_condition, command_span)? {
+                ExpressionValue::Boolean(true) => output.extend(iter::once(item)),
+                ExpressionValue::Boolean(false) => {}
+                ExpressionValue::Integer(integer) => {
+                    return Err(command_span.error(format!(
+                        "The filter condition is expected to evaluate to true or false, but evaluated to {}",
+                        integer
+                    )))
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+pub(crate) struct FoldCommand;
+
+impl CommandDefinition for FoldCommand {
+    const COMMAND_NAME: &'static str = "fold";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        const EXPECTED: &str =
+            "A fold is expected to look like [!fold! #acc = init, #x in [..] => { .. }]";
+        let mut tokens = argument.tokens();
+        let accumulator = match parse_variable_set(&mut tokens) {
+            Some(accumulator) => accumulator.to_string(),
+            None => return Err(command_span.error(EXPECTED)),
+        };
+        let initial_tokens = match parse_until_punct(&mut tokens, ',') {
+            Some(initial_tokens) => initial_tokens,
+            None => return Err(command_span.error(EXPECTED)),
+        };
+        let mut accumulated = interpreter.interpret_tokens(Tokens::new(initial_tokens))?;
+        let (variable, items) =
+            parse_variable_in_list(interpreter, &mut tokens, "=>", command_span)?;
+        let body = parse_body(&mut tokens, command_span)?;
+        for item in items {
+            let bindings = vec![
+                (accumulator.clone(), accumulated),
+                (variable.clone(), TokenStream::from(item)),
+            ];
+            accumulated =
+                interpreter.interpret_tokens_in_scope(bindings, Tokens::new(body.clone()))?;
+        }
+        Ok(accumulated)
+    }
+}
+
+//=============================
+// Ordering and de-dup commands
+//=============================
+
+/// Compares sort keys numerically if they are both single integer literals, or else by their
+/// concatenated string form. Integer keys are ordered before any other keys, so that the
+/// ordering is total.
+fn compare_keys(left: &TokenStream, right: &TokenStream) -> std::cmp::Ordering {
+    fn as_integer(key: &TokenStream) -> Option<i128> {
+        let mut tokens = key.clone().into_iter();
+        match (tokens.next(), tokens.next()) {
+            (Some(TokenTree::Literal(literal)), None) => IntegerValue::parse(&literal)
+                .ok()
+                .flatten()
+                .map(|integer| integer.value),
+            _ => None,
+        }
+    }
+    match (as_integer(left), as_integer(right)) {
+        (Some(left), Some(right)) => left.cmp(&right),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => concat_recursive(left.clone()).cmp(&concat_recursive(right.clone())),
+    }
+}
+
+pub(crate) struct SortCommand;
+
+impl CommandDefinition for SortCommand {
+    const COMMAND_NAME: &'static str = "sort";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        _command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut items = interpret_to_token_trees(interpreter, argument)?;
+        items.sort_by(|left, right| compare_keys(&left.clone().into(), &right.clone().into()));
+        Ok(items.into_iter().collect())
+    }
+}
+
+pub(crate) struct SortByKeyCommand;
+
+impl CommandDefinition for SortByKeyCommand {
+    const COMMAND_NAME: &'static str = "sort_by_key";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = argument.tokens();
+        let (variable, items) =
+            parse_variable_in_list(interpreter, &mut tokens, "=>", command_span)?;
+        let body = parse_body(&mut tokens, command_span)?;
+        let mut keyed_items = Vec::with_capacity(items.len());
+        for item in items {
+            let key = interpreter.interpret_tokens_in_scope(
+                iter::once((variable.clone(), TokenStream::from(item.clone()))),
+                Tokens::new(body.clone()),
+            )?;
+            keyed_items.push((key, item));
+        }
+        keyed_items.sort_by(|(left, _), (right, _)| compare_keys(left, right));
+        Ok(keyed_items.into_iter().map(|(_, item)| item).collect())
+    }
+}
+
+pub(crate) struct UniqueCommand;
+
+impl CommandDefinition for UniqueCommand {
+    const COMMAND_NAME: &'static str = "unique";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        _command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut unique_items: Vec<TokenTree> = Vec::new();
+        for item in interpret_to_token_trees(interpreter, argument)? {
+            if !unique_items
+                .iter()
+                .any(|existing| token_trees_eq(existing, &item))
+            {
+                unique_items.push(item);
+            }
+        }
+        Ok(unique_items.into_iter().collect())
+    }
+}
+
+pub(crate) struct ReverseCommand;
+
+impl CommandDefinition for ReverseCommand {
+    const COMMAND_NAME: &'static str = "reverse";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        _command_span: Span,
+    ) -> Result<TokenStream> {
+        Ok(interpret_to_token_trees(interpreter, argument)?
+            .into_iter()
+            .rev()
+            .collect())
+    }
+}
+
+//====================
+// Set-style commands
+//====================
+
+fn list_contains(list: &[TokenTree], item: &TokenTree) -> bool {
+    list.iter().any(|existing| token_trees_eq(existing, item))
+}
+
+/// Outputs the items which pass the filter, skipping any already output.
+fn set_output<'a>(
+    items: impl IntoIterator<Item = &'a TokenTree>,
+    filter: impl Fn(&TokenTree) -> bool,
+) -> TokenStream {
+    let mut output: Vec<TokenTree> = Vec::new();
+    for item in items {
+        if filter(item) && !list_contains(&output, item) {
+            output.push(item.clone());
+        }
+    }
+    output.into_iter().collect()
+}
+
+pub(crate) struct UnionCommand;
+
+impl CommandDefinition for UnionCommand {
+    const COMMAND_NAME: &'static str = "union";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let lists = parse_lists(Tokens::new(argument.interpret(interpreter)?), command_span)?;
+        Ok(set_output(lists.iter().flatten(), |_| true))
+    }
+}
+
+pub(crate) struct IntersectionCommand;
+
+impl CommandDefinition for IntersectionCommand {
+    const COMMAND_NAME: &'static str = "intersection";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let lists = parse_lists(Tokens::new(argument.interpret(interpreter)?), command_span)?;
+        let (first, others) = lists.split_first().expect("There is at least one list");
+        Ok(set_output(first, |item| {
+            others.iter().all(|other| list_contains(other, item))
+        }))
+    }
+}
+
+pub(crate) struct DifferenceCommand;
+
+impl CommandDefinition for DifferenceCommand {
+    const COMMAND_NAME: &'static str = "difference";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let lists = parse_lists(Tokens::new(argument.interpret(interpreter)?), command_span)?;
+        let (first, others) = lists.split_first().expect("There is at least one list");
+        Ok(set_output(first, |item| {
+            !others.iter().any(|other| list_contains(other, item))
+        }))
+    }
+}
+
+pub(crate) struct ContainsCommand;
+
+impl CommandDefinition for ContainsCommand {
+    const COMMAND_NAME: &'static str = "contains";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        const EXPECTED: &str =
+            "Expected a list and then a single item, such as [!contains! [a b c] b]";
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let list: Vec<TokenTree> = match tokens.next() {
+            Some(TokenTree::Group(group)) => group.stream().into_iter().collect(),
+            _ => return Err(command_span.error(EXPECTED)),
+        };
+        let item = match (tokens.next(), tokens.next()) {
+            (Some(item), None) => item,
+            _ => return Err(command_span.error(EXPECTED)),
+        };
+        let contains = list_contains(&list, &item);
+        Ok(TokenTree::Ident(boolean_ident(contains, command_span)).into())
+    }
+}
+
+//=====================
+// Grouping commands
+//=====================
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RemainderPolicy {
+    Keep,
+    Drop,
+    Error,
+}
+
+/// The parsed form of `N [by SEP] [remainder = keep|drop|error] from ..`
+struct GroupingArguments {
+    size: usize,
+    separator: Option<TokenTree>,
+    remainder: RemainderPolicy,
+    items: Vec<TokenStream>,
+}
+
+impl GroupingArguments {
+    fn parse(mut tokens: Tokens, allow_remainder: bool, span: Span) -> Result<Self> {
+        let size = parse_usize(tokens.next(), span)?;
+        if size == 0 {
+            return Err(span.error("The size must be greater than 0"));
+        }
+        let mut separator = None;
+        let mut remainder = RemainderPolicy::Keep;
+        loop {
+            match tokens.next() {
+                Some(TokenTree::Ident(ident)) if ident == "from" => break,
+                Some(TokenTree::Ident(ident)) if ident == "by" => match tokens.next() {
+                    Some(token_tree) => separator = Some(token_tree),
+                    None => return Err(span.error("Expected a separator after `by`")),
+                },
+                Some(TokenTree::Ident(ident)) if ident == "remainder" && allow_remainder => {
+                    if tokens.next_as_punct_matching('=').is_none() {
+                        return Err(ident.span().error("Expected `remainder = keep|drop|error`"));
+                    }
+                    remainder = match tokens.next_as_ident() {
+                        Some(policy) if policy == "keep" => RemainderPolicy::Keep,
+                        Some(policy) if policy == "drop" => RemainderPolicy::Drop,
+                        Some(policy) if policy == "error" => RemainderPolicy::Error,
+                        _ => {
+                            return Err(ident
+                                .span()
+                                .error("Expected `remainder = keep|drop|error`"))
+                        }
+                    };
+                }
+                _ => {
+                    let options = if allow_remainder {
+                        "`by <separator>`, `remainder = keep|drop|error`"
+                    } else {
+                        "`by <separator>`"
+                    };
+                    return Err(span.error(format!(
+                        "Expected the size, then optionally {}, then `from` and the input",
+                        options
+                    )));
+                }
+            }
+        }
+        let rest = iter::from_fn(|| tokens.next());
+        let items = match &separator {
+            Some(separator) => split_top_level(rest, separator),
+            None => rest.map(TokenStream::from).collect(),
+        };
+        Ok(Self {
+            size,
+            separator,
+            remainder,
+            items,
+        })
+    }
+
+    /// Outputs the items in a `(..)` group, with any separator between them.
+    fn group(&self, items: &[TokenStream], span: Span) -> TokenTree {
+        let mut contents = TokenStream::new();
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                contents.extend(self.separator.clone());
+            }
+            contents.extend(item.clone());
+        }
+        let mut group = Group::new(Delimiter::Parenthesis, contents);
+        group.set_span(span);
+        TokenTree::Group(group)
+    }
+}
+
+pub(crate) struct ChunksCommand;
+
+impl CommandDefinition for ChunksCommand {
+    const COMMAND_NAME: &'static str = "chunks";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        let arguments = GroupingArguments::parse(tokens, true, command_span)?;
+        let remainder_length = arguments.items.len() % arguments.size;
+        if remainder_length > 0 && arguments.remainder == RemainderPolicy::Error {
+            return Err(command_span.error(format!(
+                "The {} items can't be split exactly into chunks of {}, leaving a remainder of {}",
+                arguments.items.len(),
+                arguments.size,
+                remainder_length,
+            )));
+        }
+        Ok(arguments
+            .items
+            .chunks(arguments.size)
+            .filter(|chunk| {
+                chunk.len() == arguments.size || arguments.remainder == RemainderPolicy::Keep
+            })
+            .map(|chunk| arguments.group(chunk, command_span))
+            .collect())
+    }
+}
+
+pub(crate) struct WindowsCommand;
+
+impl CommandDefinition for WindowsCommand {
+    const COMMAND_NAME: &'static str = "windows";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        let arguments = GroupingArguments::parse(tokens, false, command_span)?;
+        Ok(arguments
+            .items
+            .windows(arguments.size)
+            .map(|window| arguments.group(window, command_span))
+            .collect())
+    }
+}
+
+pub(crate) struct PrefixesCommand;
+
+impl CommandDefinition for PrefixesCommand {
+    const COMMAND_NAME: &'static str = "prefixes";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let items = interpret_to_token_trees(interpreter, argument)?;
+        Ok((1..=items.len())
+            .map(|length| parenthesized(items[..length].iter().cloned(), command_span))
+            .collect())
+    }
+}
+
+pub(crate) struct SuffixesCommand;
+
+impl CommandDefinition for SuffixesCommand {
+    const COMMAND_NAME: &'static str = "suffixes";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let items = interpret_to_token_trees(interpreter, argument)?;
+        Ok((0..items.len())
+            .rev()
+            .map(|start| parenthesized(items[start..].iter().cloned(), command_span))
+            .collect())
+    }
+}
@@ -1,16 +1,36 @@
+mod closure_commands;
 mod concat_commands;
 mod core_commands;
+mod float_commands;
+mod integer_commands;
+mod list_commands;
+mod predicate_commands;
+mod token_commands;
 
 use crate::internal_prelude::*;
+use closure_commands::*;
 use concat_commands::*;
 use core_commands::*;
+use float_commands::*;
+use integer_commands::*;
+use list_commands::*;
+use predicate_commands::*;
+use token_commands::*;
 
 define_commands! {
     pub(crate) enum CommandKind {
         // Core Commands
         SetCommand,
+        SetLazyCommand,
+        GetCommand,
+        SetNamedCommand,
         RawCommand,
         IgnoreCommand,
+        ExportCommand,
+
+        // Closure Commands
+        FnCommand,
+        CallCommand,
 
         // Concat & Type Convert Commands
         StringCommand,
@@ -20,6 +40,12 @@ define_commands! {
         IdentUpperSnakeCommand,
         LiteralCommand,
 
+        // Literal Introspection Commands
+        UnsuffixedCommand,
+        SuffixOfCommand,
+        WithSuffixCommand,
+        LiteralValueCommand,
+
         // Concat & String Convert Commands
         UpperCommand,
         LowerCommand,
@@ -34,5 +60,87 @@ define_commands! {
         DecapitalizeCommand,
         TitleCommand,
         InsertSpacesCommand,
+
+        // Token Stream Commands
+        SkipCommand,
+        TakeCommand,
+        NthCommand,
+        FirstCommand,
+        LastCommand,
+        SliceCommand,
+        UngroupCommand,
+        GroupCommand,
+        RegroupCommand,
+        DelimiterOfCommand,
+        FlattenCommand,
+        TransparentGroupsCommand,
+        SplitCommand,
+        JoinCommand,
+        JoinStringCommand,
+        IntersperseCommand,
+        ReplaceCommand,
+        LenCommand,
+        CountCommand,
+        IsEmptyCommand,
+
+        // List Commands
+        ZipCommand,
+        CartesianCommand,
+        MapCommand,
+        FilterCommand,
+        FoldCommand,
+        SortCommand,
+        SortByKeyCommand,
+        UniqueCommand,
+        ReverseCommand,
+        UnionCommand,
+        IntersectionCommand,
+        DifferenceCommand,
+        ContainsCommand,
+        ChunksCommand,
+        WindowsCommand,
+        PrefixesCommand,
+        SuffixesCommand,
+
+        // Integer Commands
+        RangeCommand,
+        HexCommand,
+        BinCommand,
+        OctCommand,
+        BitAndCommand,
+        BitOrCommand,
+        XorCommand,
+        ShlCommand,
+        ShrCommand,
+        PowCommand,
+        BitMaskCommand,
+        MinCommand,
+        MaxCommand,
+        SumCommand,
+        ProductCommand,
+        AbsCommand,
+        ClampCommand,
+
+        // Float Commands
+        FloatAddCommand,
+        FloatSubCommand,
+        FloatMulCommand,
+        FloatDivCommand,
+        FloatSqrtCommand,
+        FloatFloorCommand,
+        FloatCeilCommand,
+        FloatRoundCommand,
+        FloatFormatCommand,
+
+        // Predicate Commands
+        IsIdentCommand,
+        IsLiteralCommand,
+        IsGroupCommand,
+        IsPunctCommand,
+        IsKeywordCommand,
+        IsIntCommand,
+        IsFloatCommand,
+        IsStrCommand,
+        IsBoolCommand,
     }
 }
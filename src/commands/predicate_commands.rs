@@ -0,0 +1,194 @@
+use crate::internal_prelude::*;
+
+//========
+// Helpers
+//========
+
+/// Interprets the argument, which must be a single token tree, and outputs `true` or `false`
+/// according to the predicate.
+fn execute_predicate(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+    predicate: impl Fn(&TokenTree) -> bool,
+) -> Result<TokenStream> {
+    let token_tree = parse_single_token_tree(argument.interpret(interpreter)?, command_span)?;
+    Ok(TokenTree::Ident(boolean_ident(predicate(&token_tree), command_span)).into())
+}
+
+fn parse_lit(token_tree: &TokenTree) -> Option<Lit> {
+    match token_tree {
+        TokenTree::Literal(literal) => Some(Lit::new(literal.clone())),
+        _ => None,
+    }
+}
+
+/// The strict and reserved keywords of rust, across all editions.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+    "override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+//============================
+// Token tree kind predicates
+//============================
+
+pub(crate) struct IsIdentCommand;
+
+impl CommandDefinition for IsIdentCommand {
+    const COMMAND_NAME: &'static str = "is_ident";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(token_tree, TokenTree::Ident(_))
+        })
+    }
+}
+
+pub(crate) struct IsLiteralCommand;
+
+impl CommandDefinition for IsLiteralCommand {
+    const COMMAND_NAME: &'static str = "is_literal";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(token_tree, TokenTree::Literal(_))
+        })
+    }
+}
+
+pub(crate) struct IsGroupCommand;
+
+impl CommandDefinition for IsGroupCommand {
+    const COMMAND_NAME: &'static str = "is_group";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(token_tree, TokenTree::Group(_))
+        })
+    }
+}
+
+pub(crate) struct IsPunctCommand;
+
+impl CommandDefinition for IsPunctCommand {
+    const COMMAND_NAME: &'static str = "is_punct";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(token_tree, TokenTree::Punct(_))
+        })
+    }
+}
+
+pub(crate) struct IsKeywordCommand;
+
+impl CommandDefinition for IsKeywordCommand {
+    const COMMAND_NAME: &'static str = "is_keyword";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(
+            interpreter,
+            argument,
+            command_span,
+            |token_tree| match token_tree {
+                TokenTree::Ident(ident) => KEYWORDS.iter().any(|keyword| ident == keyword),
+                _ => false,
+            },
+        )
+    }
+}
+
+//====================
+// Literal predicates
+//====================
+
+pub(crate) struct IsIntCommand;
+
+impl CommandDefinition for IsIntCommand {
+    const COMMAND_NAME: &'static str = "is_int";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(parse_lit(token_tree), Some(Lit::Int(_)))
+        })
+    }
+}
+
+pub(crate) struct IsFloatCommand;
+
+impl CommandDefinition for IsFloatCommand {
+    const COMMAND_NAME: &'static str = "is_float";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(parse_lit(token_tree), Some(Lit::Float(_)))
+        })
+    }
+}
+
+pub(crate) struct IsStrCommand;
+
+impl CommandDefinition for IsStrCommand {
+    const COMMAND_NAME: &'static str = "is_str";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(interpreter, argument, command_span, |token_tree| {
+            matches!(parse_lit(token_tree), Some(Lit::Str(_)))
+        })
+    }
+}
+
+pub(crate) struct IsBoolCommand;
+
+impl CommandDefinition for IsBoolCommand {
+    const COMMAND_NAME: &'static str = "is_bool";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        execute_predicate(
+            interpreter,
+            argument,
+            command_span,
+            |token_tree| matches!(token_tree, TokenTree::Ident(ident) if ident == "true" || ident == "false"),
+        )
+    }
+}
@@ -0,0 +1,613 @@
+use crate::internal_prelude::*;
+
+//========
+// Helpers
+//========
+
+/// Parses `N from ..` from the interpreted arguments, returning `N` and the token trees after the
+/// `from`.
+fn parse_count_from_stream(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+    command_span: Span,
+) -> Result<(usize, Vec<TokenTree>)> {
+    let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+    let count = parse_usize(tokens.next(), command_span)?;
+    parse_keyword(&mut tokens, "from", command_span)?;
+    Ok((count, iter::from_fn(|| tokens.next()).collect()))
+}
+
+fn out_of_range_error(index: usize, length: usize, span: Span) -> Error {
+    span.error(format!(
+        "The index {} is out of range for a stream of {} token tree/s",
+        index, length,
+    ))
+}
+
+//========================
+// Stream slicing commands
+//========================
+
+pub(crate) struct SkipCommand;
+
+impl CommandDefinition for SkipCommand {
+    const COMMAND_NAME: &'static str = "skip";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (count, token_trees) = parse_count_from_stream(interpreter, argument, command_span)?;
+        if count > token_trees.len() {
+            return Err(out_of_range_error(count, token_trees.len(), command_span));
+        }
+        Ok(token_trees.into_iter().skip(count).collect())
+    }
+}
+
+pub(crate) struct TakeCommand;
+
+impl CommandDefinition for TakeCommand {
+    const COMMAND_NAME: &'static str = "take";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (count, token_trees) = parse_count_from_stream(interpreter, argument, command_span)?;
+        if count > token_trees.len() {
+            return Err(out_of_range_error(count, token_trees.len(), command_span));
+        }
+        Ok(token_trees.into_iter().take(count).collect())
+    }
+}
+
+pub(crate) struct NthCommand;
+
+impl CommandDefinition for NthCommand {
+    const COMMAND_NAME: &'static str = "nth";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let (index, token_trees) = parse_count_from_stream(interpreter, argument, command_span)?;
+        let length = token_trees.len();
+        match token_trees.into_iter().nth(index) {
+            Some(token_tree) => Ok(token_tree.into()),
+            None => Err(out_of_range_error(index, length, command_span)),
+        }
+    }
+}
+
+pub(crate) struct FirstCommand;
+
+impl CommandDefinition for FirstCommand {
+    const COMMAND_NAME: &'static str = "first";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        match interpret_to_token_trees(interpreter, argument)?
+            .into_iter()
+            .next()
+        {
+            Some(token_tree) => Ok(token_tree.into()),
+            None => Err(command_span.error("The stream is empty, so has no first token tree")),
+        }
+    }
+}
+
+pub(crate) struct LastCommand;
+
+impl CommandDefinition for LastCommand {
+    const COMMAND_NAME: &'static str = "last";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        match interpret_to_token_trees(interpreter, argument)?.pop() {
+            Some(token_tree) => Ok(token_tree.into()),
+            None => Err(command_span.error("The stream is empty, so has no last token tree")),
+        }
+    }
+}
+
+pub(crate) struct SliceCommand;
+
+impl CommandDefinition for SliceCommand {
+    const COMMAND_NAME: &'static str = "slice";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let range = parse_range(&mut tokens, &["from"], command_span)?;
+        parse_keyword(&mut tokens, "from", command_span)?;
+        let token_trees: Vec<TokenTree> = iter::from_fn(|| tokens.next()).collect();
+        let length = token_trees.len();
+
+        let parse_bound = |bound: TokenStream| -> Result<Option<usize>> {
+            let mut bound_tokens = Tokens::new(bound);
+            if bound_tokens.peek().is_none() {
+                return Ok(None);
+            }
+            let bound = parse_usize(bound_tokens.next(), command_span)?;
+            match bound_tokens.next() {
+                Some(token_tree) => Err(token_tree
+                    .span()
+                    .error("Expected a single integer literal as a range bound")),
+                None => Ok(Some(bound)),
+            }
+        };
+        let start = parse_bound(range.start)?.unwrap_or(0);
+        if start > length {
+            return Err(command_span.error(format!(
+                "The range start {} is beyond the stream length {}",
+                start, length,
+            )));
+        }
+        let end = match parse_bound(range.end)? {
+            Some(end) if range.inclusive => match end.checked_add(1) {
+                Some(exclusive_end) => exclusive_end,
+                None => return Err(out_of_range_error(end, length, command_span)),
+            },
+            Some(end) => end,
+            None => length,
+        };
+        if end > length {
+            // Report the last index which would be included in the slice
+            return Err(out_of_range_error(end - 1, length, command_span));
+        }
+        if start > end {
+            return Err(command_span.error(format!(
+                "The range start {} is greater than the range end {}",
+                start, end,
+            )));
+        }
+        Ok(token_trees[start..end].iter().cloned().collect())
+    }
+}
+
+//===============
+// Group commands
+//===============
+
+const DELIMITER_NAMES: &[(&str, Delimiter)] = &[
+    ("paren", Delimiter::Parenthesis),
+    ("brace", Delimiter::Brace),
+    ("bracket", Delimiter::Bracket),
+    ("none", Delimiter::None),
+];
+
+fn parse_delimiter(token_tree: Option<TokenTree>, span: Span) -> Result<Delimiter> {
+    let expected = || {
+        let names: Vec<_> = DELIMITER_NAMES.iter().map(|(name, _)| *name).collect();
+        format!("Expected a delimiter, one of: {}", names.join(", "))
+    };
+    match token_tree {
+        Some(TokenTree::Ident(ident)) => DELIMITER_NAMES
+            .iter()
+            .find(|(name, _)| ident == name)
+            .map(|(_, delimiter)| *delimiter)
+            .ok_or_else(|| ident.span().error(expected())),
+        Some(token_tree) => Err(token_tree.span().error(expected())),
+        None => Err(span.error(expected())),
+    }
+}
+
+fn delimiter_name(delimiter: Delimiter) -> &'static str {
+    DELIMITER_NAMES
+        .iter()
+        .find(|(_, named_delimiter)| *named_delimiter == delimiter)
+        .map(|(name, _)| *name)
+        .expect("All delimiters are named")
+}
+
+/// Expects the remaining tokens to be exactly one group.
+fn parse_single_group(mut tokens: Tokens, span: Span) -> Result<Group> {
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(group)), None) => Ok(group),
+        (None, _) => Err(span.error("Expected a single group, but the input was empty")),
+        (Some(token_tree), None) => Err(token_tree.span().error(format!(
+            "Expected a single group, but found `{}`",
+            token_tree
+        ))),
+        (Some(_), Some(extra)) => Err(extra
+            .span()
+            .error("Expected a single group, but found more than one token tree")),
+    }
+}
+
+fn new_group(delimiter: Delimiter, stream: TokenStream, span: Span) -> TokenStream {
+    let mut group = Group::new(delimiter, stream);
+    group.set_span(span);
+    TokenTree::Group(group).into()
+}
+
+pub(crate) struct UngroupCommand;
+
+impl CommandDefinition for UngroupCommand {
+    const COMMAND_NAME: &'static str = "ungroup";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        Ok(parse_single_group(tokens, command_span)?.stream())
+    }
+}
+
+pub(crate) struct GroupCommand;
+
+impl CommandDefinition for GroupCommand {
+    const COMMAND_NAME: &'static str = "group";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let delimiter = parse_delimiter(tokens.next(), command_span)?;
+        Ok(new_group(
+            delimiter,
+            tokens.into_token_stream(),
+            command_span,
+        ))
+    }
+}
+
+pub(crate) struct RegroupCommand;
+
+impl CommandDefinition for RegroupCommand {
+    const COMMAND_NAME: &'static str = "regroup";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let delimiter = parse_delimiter(tokens.next(), command_span)?;
+        let group = parse_single_group(tokens, command_span)?;
+        Ok(new_group(delimiter, group.stream(), command_span))
+    }
+}
+
+pub(crate) struct DelimiterOfCommand;
+
+impl CommandDefinition for DelimiterOfCommand {
+    const COMMAND_NAME: &'static str = "delimiter_of";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let tokens = Tokens::new(argument.interpret(interpreter)?);
+        let group = parse_single_group(tokens, command_span)?;
+        let name = delimiter_name(group.delimiter());
+        Ok(TokenTree::Ident(Ident::new(name, command_span)).into())
+    }
+}
+
+pub(crate) struct FlattenCommand;
+
+impl CommandDefinition for FlattenCommand {
+    const COMMAND_NAME: &'static str = "flatten";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        _command_span: Span,
+    ) -> Result<TokenStream> {
+        Ok(flatten_transparent_groups(argument.interpret(interpreter)?))
+    }
+}
+
+pub(crate) struct TransparentGroupsCommand;
+
+impl CommandDefinition for TransparentGroupsCommand {
+    const COMMAND_NAME: &'static str = "transparent_groups";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        const EXPECTED: &str =
+            "Expected [!transparent_groups! strip] or [!transparent_groups! keep]";
+        let mut tokens = argument.tokens();
+        let strip = match (tokens.next_as_ident(), tokens.next()) {
+            (Some(ident), None) if ident == "strip" => true,
+            (Some(ident), None) if ident == "keep" => false,
+            _ => return Err(command_span.error(EXPECTED)),
+        };
+        interpreter.set_strip_transparent_groups(strip);
+        Ok(TokenStream::new())
+    }
+}
+
+//===================
+// Separator commands
+//===================
+
+pub(crate) struct SplitCommand;
+
+impl CommandDefinition for SplitCommand {
+    const COMMAND_NAME: &'static str = "split";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let separator = match tokens.next() {
+            Some(separator) => separator,
+            None => return Err(command_span.error("Expected a separator, such as `,`")),
+        };
+        let segments = split_top_level(iter::from_fn(|| tokens.next()), &separator);
+        Ok(segments
+            .into_iter()
+            .flat_map(|segment| new_group(Delimiter::None, segment, command_span))
+            .collect())
+    }
+}
+
+/// Parses a separator, which is either a single token tree, or the contents of a `(..)` group.
+fn parse_separator(token_tree: Option<TokenTree>, span: Span) -> Result<TokenStream> {
+    match token_tree {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis => {
+            Ok(group.stream())
+        }
+        Some(token_tree) => Ok(token_tree.into()),
+        None => Err(span.error("Expected a separator, such as `,` or `(+)`")),
+    }
+}
+
+/// Outputs the items with the separator between each, where any transparent group item (such as
+/// is output by `[!split! ..]`) is replaced by its contents.
+fn join_items(separator: TokenStream, items: impl Iterator<Item = TokenTree>) -> TokenStream {
+    let mut output = TokenStream::new();
+    for (index, item) in items.enumerate() {
+        if index > 0 {
+            output.extend(separator.clone());
+        }
+        match item {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                output.extend(group.stream())
+            }
+            item => output.extend(iter::once(item)),
+        }
+    }
+    output
+}
+
+pub(crate) struct JoinCommand;
+
+impl CommandDefinition for JoinCommand {
+    const COMMAND_NAME: &'static str = "join";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let separator = parse_separator(tokens.next(), command_span)?;
+        Ok(join_items(separator, iter::from_fn(|| tokens.next())))
+    }
+}
+
+pub(crate) struct JoinStringCommand;
+
+impl CommandDefinition for JoinStringCommand {
+    const COMMAND_NAME: &'static str = "join_string";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let separator = parse_separator(tokens.next(), command_span)?;
+        let joined = concat_recursive(join_items(separator, iter::from_fn(|| tokens.next())));
+        let mut literal = Literal::string(&joined);
+        literal.set_span(command_span);
+        Ok(TokenTree::Literal(literal).into())
+    }
+}
+
+pub(crate) struct IntersperseCommand;
+
+impl CommandDefinition for IntersperseCommand {
+    const COMMAND_NAME: &'static str = "intersperse";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let separator = parse_separator(tokens.next(), command_span)?;
+        let mut output = TokenStream::new();
+        for (index, token_tree) in iter::from_fn(|| tokens.next()).enumerate() {
+            if index > 0 {
+                output.extend(separator.clone());
+            }
+            output.extend(iter::once(token_tree));
+        }
+        Ok(output)
+    }
+}
+
+//=================
+// Replace commands
+//=================
+
+/// Replaces each occurrence of the pattern sequence of token trees with the replacement.
+/// If `recursive` is set, it also replaces occurrences inside groups.
+fn replace_pattern(
+    stream: TokenStream,
+    pattern: &[TokenTree],
+    replacement: &TokenStream,
+    recursive: bool,
+) -> TokenStream {
+    let token_trees: Vec<TokenTree> = stream.into_iter().collect();
+    let mut output = TokenStream::new();
+    let mut index = 0;
+    while index < token_trees.len() {
+        let remaining = &token_trees[index..];
+        let is_match = remaining.len() >= pattern.len()
+            && pattern
+                .iter()
+                .zip(remaining)
+                .all(|(pattern_tree, token_tree)| token_trees_eq(pattern_tree, token_tree));
+        if is_match {
+            output.extend(replacement.clone());
+            index += pattern.len();
+            continue;
+        }
+        match &token_trees[index] {
+            TokenTree::Group(group) if recursive => {
+                let mut replaced = Group::new(
+                    group.delimiter(),
+                    replace_pattern(group.stream(), pattern, replacement, recursive),
+                );
+                replaced.set_span(group.span());
+                output.extend(iter::once(TokenTree::Group(replaced)));
+            }
+            token_tree => output.extend(iter::once(token_tree.clone())),
+        }
+        index += 1;
+    }
+    output
+}
+
+/// Parses the raw `(pattern)` or `(replacement)` group, before it is interpreted.
+fn parse_replace_part(tokens: &mut Tokens, name: &str, span: Span) -> Result<TokenStream> {
+    match tokens.next() {
+        Some(TokenTree::Group(group)) => Ok(group.stream()),
+        _ => Err(span.error(format!(
+            "Expected the {} wrapped in a group, such as `[!replace! (Self) => (MyType) in ..]`",
+            name
+        ))),
+    }
+}
+
+/// Executes `[top_level,] (pattern) => (replacement) in ..`. Each part is split out before it is
+/// interpreted, so the parts can contain `=>` or `in` after interpretation.
+pub(crate) struct ReplaceCommand;
+
+impl CommandDefinition for ReplaceCommand {
+    const COMMAND_NAME: &'static str = "replace";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = argument.tokens();
+        let recursive = match tokens.peek() {
+            Some(TokenTree::Ident(ident)) if ident == "top_level" => {
+                tokens.next();
+                if tokens.next_as_punct_matching(',').is_none() {
+                    return Err(command_span.error("Expected a `,` after `top_level`"));
+                }
+                false
+            }
+            _ => true,
+        };
+        let pattern = parse_replace_part(&mut tokens, "pattern", command_span)?;
+        let is_arrow = tokens.next_as_punct_matching('=').is_some()
+            && tokens.next_as_punct_matching('>').is_some();
+        if !is_arrow {
+            return Err(command_span.error("Expected `=>` after the (pattern)"));
+        }
+        let replacement = parse_replace_part(&mut tokens, "replacement", command_span)?;
+        parse_keyword(&mut tokens, "in", command_span)?;
+        let pattern: Vec<TokenTree> = interpreter
+            .interpret_tokens(Tokens::new(pattern))?
+            .into_iter()
+            .collect();
+        if pattern.is_empty() {
+            return Err(command_span.error("The pattern to replace can't be empty"));
+        }
+        let replacement = interpreter.interpret_tokens(Tokens::new(replacement))?;
+        let stream = interpreter.interpret_tokens(tokens)?;
+        Ok(replace_pattern(stream, &pattern, &replacement, recursive))
+    }
+}
+
+//==================
+// Length commands
+//==================
+
+fn count_literal(count: usize, span: Span) -> TokenStream {
+    let mut literal = Literal::usize_unsuffixed(count);
+    literal.set_span(span);
+    TokenTree::Literal(literal).into()
+}
+
+pub(crate) struct LenCommand;
+
+impl CommandDefinition for LenCommand {
+    const COMMAND_NAME: &'static str = "len";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let length = argument.interpret(interpreter)?.into_iter().count();
+        Ok(count_literal(length, command_span))
+    }
+}
+
+pub(crate) struct CountCommand;
+
+impl CommandDefinition for CountCommand {
+    const COMMAND_NAME: &'static str = "count";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let mut tokens = Tokens::new(argument.interpret(interpreter)?);
+        let separator = match tokens.next() {
+            Some(separator) => separator,
+            None => return Err(command_span.error("Expected a separator, such as `,`")),
+        };
+        let segments = split_top_level(iter::from_fn(|| tokens.next()), &separator);
+        Ok(count_literal(segments.len(), command_span))
+    }
+}
+
+pub(crate) struct IsEmptyCommand;
+
+impl CommandDefinition for IsEmptyCommand {
+    const COMMAND_NAME: &'static str = "is_empty";
+
+    fn execute(
+        interpreter: &mut Interpreter,
+        argument: CommandArgumentStream,
+        command_span: Span,
+    ) -> Result<TokenStream> {
+        let is_empty = argument.interpret(interpreter)?.is_empty();
+        Ok(TokenTree::Ident(boolean_ident(is_empty, command_span)).into())
+    }
+}
@@ -0,0 +1,134 @@
+//! Tests of the error messages, which live in the crate rather than in `tests/`, as an erroring
+//! macro invocation would fail to compile.
+
+use crate::internal_prelude::*;
+
+fn assert_error(input: &str, expected_message: &str) {
+    let tokens = TokenStream::from_str(input).expect("The test input should be valid tokens");
+    match interpret(tokens) {
+        Ok(output) => panic!("Expected an error, but the output was: {}", output),
+        Err(error) => assert_eq!(error.to_string(), expected_message),
+    }
+}
+
+#[test]
+fn test_lazy_variable_errors() {
+    assert_error(
+        "[!set_lazy! #a = #b] [!set_lazy! #b = #a] #a",
+        "The lazy variable #a refers to itself: #a -> #b -> #a",
+    );
+    assert_error(
+        "[!set_lazy! #a = x #a] [!string! #a]",
+        "The lazy variable #a refers to itself: #a -> #a",
+    );
+}
+
+#[test]
+fn test_closure_errors() {
+    assert_error(
+        "[!set! #f = [!fn! () { [!call! #f] }]] [!call! #f]",
+        "Closure call depth exceeded 64 (is the closure recursive?)",
+    );
+}
+
+#[test]
+fn test_slice_errors() {
+    assert_error(
+        "[!slice! 0..=18446744073709551615 from a]",
+        "The index 18446744073709551615 is out of range for a stream of 1 token tree/s",
+    );
+    assert_error(
+        "[!slice! 5.. from a b]",
+        "The range start 5 is beyond the stream length 2",
+    );
+    assert_error(
+        "[!slice! 3..1 from a b c]",
+        "The range start 3 is greater than the range end 1",
+    );
+}
+
+#[test]
+fn test_range_errors() {
+    assert_error(
+        "[!range! 0..18446744073709551615u64]",
+        "The range has 18446744073709551615 values, but at most 65536 are supported",
+    );
+    assert_error(
+        "[!range! 0..=65536]",
+        "The range has 65537 values, but at most 65536 are supported",
+    );
+    assert_error(
+        "[!range! 0u8..10 step 2u16]",
+        "Cannot combine integers of different types `u8` and `u16`",
+    );
+}
+
+#[test]
+fn test_cartesian_errors() {
+    assert_error(
+        "[!cartesian! [[!range! 0..100]] [[!range! 0..100]] [[!range! 0..100]]]",
+        "The cartesian product has 1000000 items, but at most 65536 are supported",
+    );
+}
+
+#[test]
+fn test_expression_errors() {
+    assert_error(
+        "#(1 < 2 < 3)",
+        "Comparison operators cannot be chained, so use parentheses such as `(a < b) == c`",
+    );
+    assert_error(
+        "#{1 == 1 != false}",
+        "Comparison operators cannot be chained, so use parentheses such as `(a < b) == c`",
+    );
+}
+
+#[test]
+fn test_bitwise_errors() {
+    assert_error(
+        "[!shl! 1i8 8]",
+        "Cannot shift 1i8 by 8 bits, as the shift must be between 0 and 7",
+    );
+    assert_error("[!shl! 3i8 7]", "384 is out of range for the type `i8`");
+    assert_error(
+        "[!bitand! -1 0xFFu8]",
+        "-1 is out of range for the type `u8`",
+    );
+    assert_error("#(-1 | 1u8)", "-1 is out of range for the type `u8`");
+    assert_error(
+        "[!bit_mask! 0..128u128]",
+        "The bit mask operation overflowed an i128",
+    );
+}
+
+#[test]
+fn test_aggregate_errors() {
+    assert_error(
+        "[!max! 340282366920938463463374607431768211455u128 1u128]",
+        "340282366920938463463374607431768211455u128 is outside the supported range of -170141183460469231731687303715884105728 to 170141183460469231731687303715884105727",
+    );
+    assert_error(
+        "[!sum! 200u8 100u8]",
+        "300 is out of range for the type `u8`",
+    );
+    assert_error(
+        "[!min!]",
+        "Expected at least one integer, but the input was empty",
+    );
+    assert_error(
+        "[!min! 1 a]",
+        "Expected an integer literal or a (..) group containing an expression, but found `a`",
+    );
+}
+
+#[test]
+fn test_literal_value_errors() {
+    assert_error(
+        r#"[!literal_value! b'\xff']"#,
+        r"The byte literal contains the non-ASCII byte \xff, which can't be decoded",
+    );
+    assert_error(
+        r#"[!literal_value! b"ok\xff"]"#,
+        r"The byte literal contains the non-ASCII byte \xff, which can't be decoded",
+    );
+}
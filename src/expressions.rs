@@ -0,0 +1,688 @@
+use crate::internal_prelude::*;
+
+//===================
+// Inline expressions
+//===================
+
+/// An inline expression such as `#(#i + 1)` or `#{#len > 3}`.
+pub(crate) struct InlineExpression {
+    group: Group,
+}
+
+impl InlineExpression {
+    pub(crate) fn new(group: Group) -> Self {
+        Self { group }
+    }
+
+    pub(crate) fn execute(self, interpreter: &mut Interpreter) -> Result<TokenStream> {
+        let span = self.group.span();
+        let interpreted = interpreter.interpret_tokens(Tokens::new(self.group.stream()))?;
+        evaluate_expression(interpreted, span)?.into_token_stream(span)
+    }
+}
+
+/// Evaluates an already-interpreted token stream as an expression over integer and boolean
+/// literals, with rust-like operators and precedence.
+pub(crate) fn evaluate_expression(tokens: TokenStream, span: Span) -> Result<ExpressionValue> {
+    let mut parser = ExpressionParser {
+        tokens: tokenize_expression(tokens)?,
+        position: 0,
+        span,
+    };
+    let value = parser.parse_expression(0)?;
+    match parser.tokens.get(parser.position) {
+        Some(token) => Err(token.span().error("Expected an operator")),
+        None => Ok(value),
+    }
+}
+
+//=======
+// Values
+//=======
+
+#[derive(Clone, Copy)]
+pub(crate) enum ExpressionValue {
+    Integer(IntegerValue),
+    Boolean(bool),
+}
+
+impl ExpressionValue {
+    fn parse(token_tree: &TokenTree) -> Result<Option<Self>> {
+        Ok(match token_tree {
+            TokenTree::Literal(literal) => IntegerValue::parse(literal)?.map(Self::Integer),
+            TokenTree::Ident(ident) if ident == "true" => Some(Self::Boolean(true)),
+            TokenTree::Ident(ident) if ident == "false" => Some(Self::Boolean(false)),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn into_token_stream(self, span: Span) -> Result<TokenStream> {
+        Ok(match self {
+            Self::Integer(integer) => TokenTree::Literal(integer.to_literal(span)?).into(),
+            Self::Boolean(value) => TokenTree::Ident(boolean_ident(value, span)).into(),
+        })
+    }
+
+    fn expect_boolean(self, span: Span) -> Result<bool> {
+        match self {
+            Self::Boolean(value) => Ok(value),
+            Self::Integer(integer) => Err(span.error(format!(
+                "Expected a boolean, but found the integer {}",
+                integer
+            ))),
+        }
+    }
+
+    pub(crate) fn expect_integer(self, span: Span) -> Result<IntegerValue> {
+        match self {
+            Self::Integer(integer) => Ok(integer),
+            Self::Boolean(value) => Err(span.error(format!(
+                "Expected an integer, but found the boolean {}",
+                value
+            ))),
+        }
+    }
+}
+
+pub(crate) fn boolean_ident(value: bool, span: Span) -> Ident {
+    Ident::new(if value { "true" } else { "false" }, span)
+}
+
+/// An integer, which is stored as an `i128`, and which remembers the type of any suffix so that
+/// results can be range checked against that type.
+#[derive(Clone, Copy)]
+pub(crate) struct IntegerValue {
+    pub(crate) value: i128,
+    pub(crate) kind: Option<IntegerKind>,
+}
+
+impl IntegerValue {
+    /// Returns `None` if the literal isn't an integer literal.
+    /// The value isn't range checked against its kind until it is output or used in an operation,
+    /// so that negated literals such as `-128i8` work as expected.
+    pub(crate) fn parse(literal: &Literal) -> Result<Option<Self>> {
+        let lit_int = match Lit::new(literal.clone()) {
+            Lit::Int(lit_int) => lit_int,
+            _ => return Ok(None),
+        };
+        let kind = match lit_int.suffix() {
+            "" => None,
+            suffix => Some(IntegerKind::from_suffix(suffix).ok_or_else(|| {
+                literal
+                    .span()
+                    .error(format!("`{}` is not a supported integer suffix", suffix))
+            })?),
+        };
+        let value = lit_int.base10_parse::<i128>().map_err(|_| {
+            literal.span().error(format!(
+                "{} is outside the supported range of {} to {}",
+                literal,
+                i128::MIN,
+                i128::MAX,
+            ))
+        })?;
+        Ok(Some(Self { value, kind }))
+    }
+
+    /// Combines the kinds of two integers, erroring if they have different suffixes.
+    pub(crate) fn combine_kinds(self, other: Self, span: Span) -> Result<Option<IntegerKind>> {
+        IntegerKind::combine(self.kind, other.kind, span)
+    }
+
+    /// Creates an integer from the result of a checked operation, erroring if the operation
+    /// overflowed, or if the result is out of range for the integer's kind.
+    pub(crate) fn checked(
+        value: Option<i128>,
+        kind: Option<IntegerKind>,
+        operation: &str,
+        span: Span,
+    ) -> Result<Self> {
+        let value = value
+            .ok_or_else(|| span.error(format!("The {} operation overflowed an i128", operation)))?;
+        let integer = Self { value, kind };
+        integer.check_in_range(span)?;
+        Ok(integer)
+    }
+
+    /// Shifts left by `amount` bits, erroring if the amount is at least the number of bits in the
+    /// integer's type (or in an `i128` if it's unsuffixed), or if any set bits are lost.
+    /// Like in rust, a non-negative signed integer can be shifted into its sign bit, so
+    /// `1i8 << 7` is `-128`.
+    pub(crate) fn shl(self, amount: Self, span: Span) -> Result<Self> {
+        let amount = self.shift_amount(amount, span)?;
+        let result = self
+            .value
+            .checked_shl(amount)
+            .filter(|result| result >> amount == self.value);
+        let result = match self.kind {
+            Some(kind) if kind.is_signed() && self.value >= 0 => {
+                let unused_bits = 128 - kind.bits();
+                let value = self.value as u128;
+                value
+                    .checked_shl(amount)
+                    .filter(|result| {
+                        result >> amount == value && result.leading_zeros() >= unused_bits
+                    })
+                    .map(|result| ((result << unused_bits) as i128) >> unused_bits)
+                    .or(result)
+            }
+            _ => result,
+        };
+        Self::checked(result, self.kind, "shift left", span)
+    }
+
+    /// Shifts right by `amount` bits, erroring if the amount is at least the number of bits in the
+    /// integer's type (or in an `i128` if it's unsuffixed).
+    pub(crate) fn shr(self, amount: Self, span: Span) -> Result<Self> {
+        let amount = self.shift_amount(amount, span)?;
+        Self::checked(
+            self.value.checked_shr(amount),
+            self.kind,
+            "shift right",
+            span,
+        )
+    }
+
+    fn shift_amount(self, amount: Self, span: Span) -> Result<u32> {
+        let bits = self.kind.map_or(128, IntegerKind::bits);
+        match u32::try_from(amount.value) {
+            Ok(amount) if amount < bits => Ok(amount),
+            _ => Err(span.error(format!(
+                "Cannot shift {} by {} bits, as the shift must be between 0 and {}",
+                self,
+                amount.value,
+                bits - 1,
+            ))),
+        }
+    }
+
+    /// Raises to the power of `exponent`, keeping the type of the base, like rust's `pow`.
+    pub(crate) fn pow(self, exponent: Self, span: Span) -> Result<Self> {
+        let exponent = u32::try_from(exponent.value).map_err(|_| {
+            span.error(format!(
+                "The exponent must be a non-negative u32, but was {}",
+                exponent
+            ))
+        })?;
+        Self::checked(self.value.checked_pow(exponent), self.kind, "power", span)
+    }
+
+    fn check_in_range(self, span: Span) -> Result<()> {
+        self.check_fits(self.kind, span)
+    }
+
+    /// Errors if the value is outside the range of `kind`, such as a negative value for a bitwise
+    /// operation with an unsigned integer.
+    pub(crate) fn check_fits(self, kind: Option<IntegerKind>, span: Span) -> Result<()> {
+        if let Some(kind) = kind {
+            let (min, max) = kind.bounds();
+            if self.value < min || self.value > max {
+                return Err(span.error(format!(
+                    "{} is out of range for the type `{}`",
+                    self.value,
+                    kind.suffix()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_literal(self, span: Span) -> Result<Literal> {
+        self.check_in_range(span)?;
+        let value = self.value;
+        let mut literal = match self.kind {
+            None => Literal::i128_unsuffixed(value),
+            Some(IntegerKind::I8) => Literal::i8_suffixed(value as i8),
+            Some(IntegerKind::I16) => Literal::i16_suffixed(value as i16),
+            Some(IntegerKind::I32) => Literal::i32_suffixed(value as i32),
+            Some(IntegerKind::I64) => Literal::i64_suffixed(value as i64),
+            Some(IntegerKind::I128) => Literal::i128_suffixed(value),
+            Some(IntegerKind::Isize) => Literal::isize_suffixed(value as isize),
+            Some(IntegerKind::U8) => Literal::u8_suffixed(value as u8),
+            Some(IntegerKind::U16) => Literal::u16_suffixed(value as u16),
+            Some(IntegerKind::U32) => Literal::u32_suffixed(value as u32),
+            Some(IntegerKind::U64) => Literal::u64_suffixed(value as u64),
+            Some(IntegerKind::U128) => Literal::u128_suffixed(value as u128),
+            Some(IntegerKind::Usize) => Literal::usize_suffixed(value as usize),
+        };
+        literal.set_span(span);
+        Ok(literal)
+    }
+}
+
+impl std::fmt::Display for IntegerValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            Some(kind) => write!(f, "{}{}", self.value, kind.suffix()),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IntegerKind {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+}
+
+impl IntegerKind {
+    const ALL: &'static [Self] = &[
+        Self::I8,
+        Self::I16,
+        Self::I32,
+        Self::I64,
+        Self::I128,
+        Self::Isize,
+        Self::U8,
+        Self::U16,
+        Self::U32,
+        Self::U64,
+        Self::U128,
+        Self::Usize,
+    ];
+
+    /// Combines the kinds of two integers, erroring if they are different. An unsuffixed integer
+    /// takes the kind of the other.
+    pub(crate) fn combine(
+        left: Option<Self>,
+        right: Option<Self>,
+        span: Span,
+    ) -> Result<Option<Self>> {
+        match (left, right) {
+            (Some(left), Some(right)) if left != right => Err(span.error(format!(
+                "Cannot combine integers of different types `{}` and `{}`",
+                left.suffix(),
+                right.suffix(),
+            ))),
+            (Some(kind), _) | (_, Some(kind)) => Ok(Some(kind)),
+            (None, None) => Ok(None),
+        }
+    }
+
+    pub(crate) fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.suffix() == suffix)
+    }
+
+    pub(crate) fn suffix(self) -> &'static str {
+        match self {
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::I128 => "i128",
+            Self::Isize => "isize",
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::U128 => "u128",
+            Self::Usize => "usize",
+        }
+    }
+
+    /// Whether the type can represent negative values.
+    pub(crate) fn is_signed(self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128 | Self::Isize
+        )
+    }
+
+    /// The number of bits in the type. The pointer-sized types are assumed to be 64-bit.
+    pub(crate) fn bits(self) -> u32 {
+        match self {
+            Self::I8 | Self::U8 => 8,
+            Self::I16 | Self::U16 => 16,
+            Self::I32 | Self::U32 => 32,
+            Self::I64 | Self::U64 | Self::Isize | Self::Usize => 64,
+            Self::I128 | Self::U128 => 128,
+        }
+    }
+
+    /// The inclusive bounds of the type, restricted to the `i128` space that calculations use.
+    /// The pointer-sized types are assumed to be 64-bit.
+    pub(crate) fn bounds(self) -> (i128, i128) {
+        match self {
+            Self::I8 => (i8::MIN as i128, i8::MAX as i128),
+            Self::I16 => (i16::MIN as i128, i16::MAX as i128),
+            Self::I32 => (i32::MIN as i128, i32::MAX as i128),
+            Self::I64 | Self::Isize => (i64::MIN as i128, i64::MAX as i128),
+            Self::I128 => (i128::MIN, i128::MAX),
+            Self::U8 => (0, u8::MAX as i128),
+            Self::U16 => (0, u16::MAX as i128),
+            Self::U32 => (0, u32::MAX as i128),
+            Self::U64 | Self::Usize => (0, u64::MAX as i128),
+            Self::U128 => (0, i128::MAX),
+        }
+    }
+}
+
+//==========
+// Operators
+//==========
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+}
+
+impl Operator {
+    const ALL: &'static [Self] = &[
+        Self::Add,
+        Self::Sub,
+        Self::Mul,
+        Self::Div,
+        Self::Rem,
+        Self::Eq,
+        Self::Ne,
+        Self::Lt,
+        Self::Le,
+        Self::Gt,
+        Self::Ge,
+        Self::And,
+        Self::Or,
+        Self::Not,
+        Self::BitAnd,
+        Self::BitOr,
+        Self::BitXor,
+        Self::Shl,
+        Self::Shr,
+    ];
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::And => "&&",
+            Self::Or => "||",
+            Self::Not => "!",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+        }
+    }
+
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|op| op.symbol() == symbol)
+    }
+
+    fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge
+        )
+    }
+
+    /// The binding power of the operator when used as a binary operator, following rust's
+    /// precedence rules. Higher binds more tightly.
+    fn binary_precedence(self) -> Option<u8> {
+        Some(match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge => 3,
+            Self::BitOr => 4,
+            Self::BitXor => 5,
+            Self::BitAnd => 6,
+            Self::Shl | Self::Shr => 7,
+            Self::Add | Self::Sub => 8,
+            Self::Mul | Self::Div | Self::Rem => 9,
+            Self::Not => return None,
+        })
+    }
+
+    fn apply_unary(self, value: ExpressionValue, span: Span) -> Result<ExpressionValue> {
+        Ok(match self {
+            Self::Sub => {
+                let integer = value.expect_integer(span)?;
+                ExpressionValue::Integer(IntegerValue::checked(
+                    integer.value.checked_neg(),
+                    integer.kind,
+                    "negation",
+                    span,
+                )?)
+            }
+            Self::Not => ExpressionValue::Boolean(!value.expect_boolean(span)?),
+            _ => {
+                return Err(span.error(format!(
+                    "`{}` can't be used as a unary operator",
+                    self.symbol()
+                )))
+            }
+        })
+    }
+
+    fn apply_binary(
+        self,
+        left: ExpressionValue,
+        right: ExpressionValue,
+        span: Span,
+    ) -> Result<ExpressionValue> {
+        if let (Self::Eq | Self::Ne, ExpressionValue::Boolean(l), ExpressionValue::Boolean(r)) =
+            (self, left, right)
+        {
+            return Ok(ExpressionValue::Boolean((l == r) == (self == Self::Eq)));
+        }
+        if let (
+            Self::BitAnd | Self::BitOr | Self::BitXor,
+            ExpressionValue::Boolean(l),
+            ExpressionValue::Boolean(r),
+        ) = (self, left, right)
+        {
+            return Ok(ExpressionValue::Boolean(match self {
+                Self::BitAnd => l & r,
+                Self::BitOr => l | r,
+                _ => l ^ r,
+            }));
+        }
+        if let Self::And | Self::Or = self {
+            let left = left.expect_boolean(span)?;
+            let right = right.expect_boolean(span)?;
+            return Ok(ExpressionValue::Boolean(match self {
+                Self::And => left && right,
+                _ => left || right,
+            }));
+        }
+        let left = left.expect_integer(span)?;
+        let right = right.expect_integer(span)?;
+        // Like in rust, the shift amount can be of a different type
+        match self {
+            Self::Shl => return Ok(ExpressionValue::Integer(left.shl(right, span)?)),
+            Self::Shr => return Ok(ExpressionValue::Integer(left.shr(right, span)?)),
+            _ => {}
+        }
+        let kind = left.combine_kinds(right, span)?;
+        if let Self::BitAnd | Self::BitOr | Self::BitXor = self {
+            left.check_fits(kind, span)?;
+            right.check_fits(kind, span)?;
+        }
+        let (l, r) = (left.value, right.value);
+        let (result, operation) = match self {
+            Self::Eq => return Ok(ExpressionValue::Boolean(l == r)),
+            Self::Ne => return Ok(ExpressionValue::Boolean(l != r)),
+            Self::Lt => return Ok(ExpressionValue::Boolean(l < r)),
+            Self::Le => return Ok(ExpressionValue::Boolean(l <= r)),
+            Self::Gt => return Ok(ExpressionValue::Boolean(l > r)),
+            Self::Ge => return Ok(ExpressionValue::Boolean(l >= r)),
+            Self::Add => (l.checked_add(r), "addition"),
+            Self::Sub => (l.checked_sub(r), "subtraction"),
+            Self::Mul => (l.checked_mul(r), "multiplication"),
+            Self::Div | Self::Rem if r == 0 => {
+                return Err(span.error("Attempted to divide by zero"));
+            }
+            Self::Div => (l.checked_div(r), "division"),
+            Self::Rem => (l.checked_rem(r), "remainder"),
+            Self::BitAnd => (Some(l & r), "bitwise and"),
+            Self::BitOr => (Some(l | r), "bitwise or"),
+            Self::BitXor => (Some(l ^ r), "bitwise xor"),
+            Self::And | Self::Or | Self::Not | Self::Shl | Self::Shr => unreachable!(),
+        };
+        Ok(ExpressionValue::Integer(IntegerValue::checked(
+            result, kind, operation, span,
+        )?))
+    }
+}
+
+//========
+// Parsing
+//========
+
+enum ExpressionToken {
+    Value(ExpressionValue, Span),
+    SubExpression(Group),
+    Operator(Operator, Span),
+}
+
+impl ExpressionToken {
+    fn span(&self) -> Span {
+        match self {
+            Self::Value(_, span) | Self::Operator(_, span) => *span,
+            Self::SubExpression(group) => group.span(),
+        }
+    }
+}
+
+fn tokenize_expression(tokens: TokenStream) -> Result<Vec<ExpressionToken>> {
+    let mut output = Vec::new();
+    let mut tokens = Tokens::new(tokens);
+    while let Some(token_tree) = tokens.next() {
+        let token = match token_tree {
+            TokenTree::Group(group) => match group.delimiter() {
+                Delimiter::Parenthesis | Delimiter::None => ExpressionToken::SubExpression(group),
+                _ => {
+                    return Err(group
+                        .span()
+                        .error("Only (..) groups are supported in expressions"))
+                }
+            },
+            TokenTree::Punct(punct) => {
+                let mut symbol = punct.as_char().to_string();
+                // Attempt to read a two-character operator such as `&&` or `<=`
+                if let (Spacing::Joint, Some(TokenTree::Punct(next))) =
+                    (punct.spacing(), tokens.peek())
+                {
+                    let two_char_symbol = format!("{}{}", symbol, next.as_char());
+                    if Operator::from_symbol(&two_char_symbol).is_some() {
+                        symbol = two_char_symbol;
+                        tokens.next();
+                    }
+                }
+                match Operator::from_symbol(&symbol) {
+                    Some(operator) => ExpressionToken::Operator(operator, punct.span()),
+                    None => {
+                        return Err(punct
+                            .span()
+                            .error(format!("`{}` is not a supported operator", symbol)))
+                    }
+                }
+            }
+            token_tree => match ExpressionValue::parse(&token_tree)? {
+                Some(value) => ExpressionToken::Value(value, token_tree.span()),
+                None => {
+                    return Err(token_tree.span().error(format!(
+                        "Expected an integer literal or boolean, but found `{}`",
+                        token_tree
+                    )))
+                }
+            },
+        };
+        output.push(token);
+    }
+    Ok(output)
+}
+
+struct ExpressionParser {
+    tokens: Vec<ExpressionToken>,
+    position: usize,
+    span: Span,
+}
+
+impl ExpressionParser {
+    fn parse_expression(&mut self, min_precedence: u8) -> Result<ExpressionValue> {
+        let mut left = self.parse_unary()?;
+        let mut left_is_comparison = false;
+        while let Some(ExpressionToken::Operator(operator, span)) = self.tokens.get(self.position) {
+            let (operator, span) = (*operator, *span);
+            let precedence = match operator.binary_precedence() {
+                Some(precedence) if precedence >= min_precedence => precedence,
+                Some(_) => break,
+                None => return Err(span.error("Expected a binary operator")),
+            };
+            // Like rust, we reject `a < b < c` rather than guess at its meaning
+            if left_is_comparison && operator.is_comparison() {
+                return Err(span.error(
+                    "Comparison operators cannot be chained, so use parentheses such as `(a < b) == c`",
+                ));
+            }
+            left_is_comparison = operator.is_comparison();
+            self.position += 1;
+            let right = self.parse_expression(precedence + 1)?;
+            left = operator.apply_binary(left, right, span)?;
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<ExpressionValue> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token,
+            None => {
+                return Err(self
+                    .span
+                    .error("Expected a value, but the expression ended"))
+            }
+        };
+        self.position += 1;
+        match token {
+            ExpressionToken::Value(value, _) => Ok(*value),
+            ExpressionToken::SubExpression(group) => {
+                evaluate_expression(group.stream(), group.span())
+            }
+            ExpressionToken::Operator(operator, span) => {
+                let (operator, span) = (*operator, *span);
+                let value = self.parse_unary()?;
+                operator.apply_unary(value, span)
+            }
+        }
+    }
+}
@@ -5,9 +5,11 @@ pub(crate) use syn::{parse_str, Error, Lit, Result};
 
 pub(crate) use crate::command::*;
 pub(crate) use crate::commands::*;
+pub(crate) use crate::expressions::*;
 pub(crate) use crate::interpreter::*;
 pub(crate) use crate::parsing::*;
 pub(crate) use crate::string_conversion::*;
+pub(crate) use crate::token_helpers::*;
 
 pub(crate) struct Tokens(iter::Peekable<<TokenStream as IntoIterator>::IntoIter>);
 
@@ -20,6 +22,12 @@ impl Tokens {
         self.0.peek()
     }
 
+    /// Looks `n` token trees ahead without consuming anything, where `peek_nth(0)` is the next
+    /// token tree.
+    pub(crate) fn peek_nth(&self, n: usize) -> Option<TokenTree> {
+        self.0.clone().nth(n)
+    }
+
     pub(crate) fn next(&mut self) -> Option<TokenTree> {
         self.0.next()
     }
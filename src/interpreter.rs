@@ -4,47 +4,176 @@ pub(crate) fn interpret(token_stream: TokenStream) -> Result<TokenStream> {
     Interpreter::new().interpret_tokens(Tokens::new(token_stream))
 }
 
+/// The most closure calls which can be nested, so that a recursive closure errors rather than
+/// overflowing the compiler's stack.
+const MAX_CALL_DEPTH: usize = 64;
+
 pub(crate) struct Interpreter {
-    variables: HashMap<String, TokenStream>,
+    /// A stack of variable scopes. The first scope is the global scope, and is always present.
+    scopes: Vec<HashMap<String, VariableValue>>,
+    /// The names of the lazy variables currently being evaluated, used for cycle detection.
+    lazy_evaluation_stack: Vec<String>,
+    /// The number of closure calls currently being evaluated.
+    call_depth: usize,
+    /// Whether transparent groups (with [`Delimiter::None`]) in the source are replaced by their
+    /// contents. Such groups typically wrap metavariables substituted by `macro_rules!`.
+    strip_transparent_groups: bool,
+}
+
+pub(crate) enum VariableValue {
+    /// An already-interpreted token stream, output as-is on substitution.
+    Eager(TokenStream),
+    /// An uninterpreted token stream, which is interpreted afresh on each substitution.
+    Lazy(TokenStream),
 }
 
 impl Interpreter {
     pub(crate) fn new() -> Self {
         Self {
-            variables: Default::default(),
+            scopes: vec![Default::default()],
+            lazy_evaluation_stack: Default::default(),
+            call_depth: 0,
+            strip_transparent_groups: false,
+        }
+    }
+
+    pub(crate) fn set_strip_transparent_groups(&mut self, strip_transparent_groups: bool) {
+        self.strip_transparent_groups = strip_transparent_groups;
+    }
+
+    /// Interprets the tokens in a fresh scope, in which the given variables are bound.
+    ///
+    /// The bindings (and any other variables first defined in the scope) are dropped afterwards,
+    /// but assignments to variables of enclosing scopes still apply to them.
+    pub(crate) fn interpret_tokens_in_scope(
+        &mut self,
+        bindings: impl IntoIterator<Item = (String, TokenStream)>,
+        source_tokens: Tokens,
+    ) -> Result<TokenStream> {
+        let mut scope = HashMap::new();
+        for (name, tokens) in bindings {
+            scope.insert(name, VariableValue::Eager(tokens));
+        }
+        self.scopes.push(scope);
+        let result = self.interpret_tokens(source_tokens);
+        self.scopes.pop();
+        result
+    }
+
+    /// Interprets the body of a closure in a fresh scope, in which its parameters are bound.
+    /// Errors if closure calls are nested more than [`MAX_CALL_DEPTH`] deep.
+    pub(crate) fn interpret_call(
+        &mut self,
+        bindings: impl IntoIterator<Item = (String, TokenStream)>,
+        body: Tokens,
+        span: Span,
+    ) -> Result<TokenStream> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(span.error(format!(
+                "Closure call depth exceeded {} (is the closure recursive?)",
+                MAX_CALL_DEPTH
+            )));
         }
+        self.call_depth += 1;
+        let result = self.interpret_tokens_in_scope(bindings, body);
+        self.call_depth -= 1;
+        result
     }
 
     pub(crate) fn set_variable(&mut self, name: String, tokens: TokenStream) {
-        self.variables.insert(name, tokens);
+        self.assign_variable(name, VariableValue::Eager(tokens));
+    }
+
+    pub(crate) fn set_lazy_variable(&mut self, name: String, uninterpreted_tokens: TokenStream) {
+        self.assign_variable(name, VariableValue::Lazy(uninterpreted_tokens));
+    }
+
+    /// Updates the variable in the innermost scope it is defined in, or else defines it in the
+    /// innermost scope.
+    fn assign_variable(&mut self, name: String, value: VariableValue) {
+        let scope = match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.contains_key(&name))
+        {
+            Some(scope) => scope,
+            None => self.innermost_scope(),
+        };
+        scope.insert(name, value);
+    }
+
+    fn innermost_scope(&mut self) -> &mut HashMap<String, VariableValue> {
+        self.scopes
+            .last_mut()
+            .expect("The global scope is always present")
+    }
+
+    /// Returns the variable's definition, without interpreting it if it's lazy.
+    pub(crate) fn find_variable(&self, name: &str) -> Option<&VariableValue> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
     }
 
-    pub(crate) fn get_variable(&self, name: &str) -> Option<&TokenStream> {
-        self.variables.get(name)
+    /// Returns the value of the variable, or `None` if it isn't set.
+    /// Lazy variables are interpreted against the current state of the interpreter.
+    pub(crate) fn get_variable(&mut self, name: &str, span: Span) -> Result<Option<TokenStream>> {
+        let uninterpreted_tokens = match self.find_variable(name) {
+            Some(VariableValue::Eager(tokens)) => return Ok(Some(tokens.clone())),
+            Some(VariableValue::Lazy(uninterpreted_tokens)) => uninterpreted_tokens.clone(),
+            None => return Ok(None),
+        };
+        if let Some(cycle_start) = self
+            .lazy_evaluation_stack
+            .iter()
+            .position(|evaluating| evaluating == name)
+        {
+            let cycle = self.lazy_evaluation_stack[cycle_start..]
+                .iter()
+                .chain(iter::once(&name.to_string()))
+                .map(|name| format!("#{}", name))
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(span.error(format!(
+                "The lazy variable #{} refers to itself: {}",
+                name, cycle,
+            )));
+        }
+        self.lazy_evaluation_stack.push(name.to_string());
+        let result = self.interpret_tokens(Tokens::new(uninterpreted_tokens));
+        self.lazy_evaluation_stack.pop();
+        result.map(Some)
     }
 
     pub(crate) fn interpret_tokens(&mut self, mut source_tokens: Tokens) -> Result<TokenStream> {
         let mut expanded = TokenStream::new();
         loop {
             match parse_next_item(&mut source_tokens)? {
-                NextItem::Leaf(token_tree) => {
-                    expanded.extend(iter::once(token_tree));
-                }
-                NextItem::Group(group) => {
-                    expanded.extend(iter::once(TokenTree::Group(Group::new(
-                        group.delimiter(),
-                        // If it's a group, run interpret on its contents recursively.
-                        self.interpret_tokens(Tokens::new(group.stream()))?,
-                    ))));
-                }
-                NextItem::VariableSubstitution(variable_substitution) => {
-                    expanded.extend(variable_substitution.execute(self)?);
-                }
-                NextItem::CommandInvocation(command_invocation) => {
-                    expanded.extend(command_invocation.execute(self)?);
-                }
                 NextItem::EndOfStream => return Ok(expanded),
+                item => expanded.extend(self.interpret_item(item)?),
             }
         }
     }
+
+    /// Interprets a single item, as parsed by [`parse_next_item`].
+    pub(crate) fn interpret_item(&mut self, item: NextItem) -> Result<TokenStream> {
+        Ok(match item {
+            NextItem::Leaf(token_tree) => TokenStream::from(token_tree),
+            NextItem::Group(group)
+                if group.delimiter() == Delimiter::None && self.strip_transparent_groups =>
+            {
+                self.interpret_tokens(Tokens::new(group.stream()))?
+            }
+            NextItem::Group(group) => TokenStream::from(TokenTree::Group(Group::new(
+                group.delimiter(),
+                // If it's a group, run interpret on its contents recursively.
+                self.interpret_tokens(Tokens::new(group.stream()))?,
+            ))),
+            NextItem::VariableSubstitution(variable_substitution) => {
+                variable_substitution.execute(self)?
+            }
+            NextItem::InlineExpression(inline_expression) => inline_expression.execute(self)?,
+            NextItem::CommandInvocation(command_invocation) => command_invocation.execute(self)?,
+            NextItem::EndOfStream => TokenStream::new(),
+        })
+    }
 }
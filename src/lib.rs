@@ -121,9 +121,18 @@
 //! ### Special commands
 //!
 //! * `[!set! #foo = Hello]` followed by `[!set! #foo = #bar(World)]` sets the variable `#foo` to the token stream `Hello` and `#bar` to the token stream `Hello(World)`, and outputs no tokens. Using `#foo` or `#bar` later on will output the current value in the corresponding variable.
+//! * `[!set_lazy! #full_name = #prefix #name]` defines a lazy variable, and outputs no tokens. Its tokens are stored uninterpreted, and are interpreted afresh against the current variables each time `#full_name` is used. A lazy variable which refers back to itself results in an error.
+//! * `[!set_named! field_ #i _ty = u32]` works like `[!set! ..]`, but the variable name is built by interpreting and concatenating the tokens before the `=`, following the same rules as `[!ident! ..]`. For example, if `#i` is `0`, this sets `#field_0_ty`.
+//! * `[!get! field_ #i _ty]` interprets and concatenates its tokens into a variable name, in the same way, and outputs that variable's value.
+//! * `[!export! my_definitions = #foo #bar]` outputs a `macro_rules! my_definitions` declaration. Invoking `my_definitions! { ... }` expands to a `preinterpret!` call which first sets `#foo` and `#bar` to their values at the point of export (lazy variables stay lazy), and then interprets the provided body. Put `#[macro_export]` before the command to share your definitions with other crates.
 //! * `[!raw! abc #abc [!ident! test]]` outputs its contents as-is, without any interpretation, giving the token stream `abc #abc [!ident! test]`.
 //! * `[!ignore! $foo]` ignores all of its content and outputs no tokens. It is useful to make a declarative macro loop over a meta-variable without outputting it into the resulting stream.
 //!
+//! ### Closure commands
+//!
+//! * `[!fn! (#a #b) { [!string! #a "-" #b] }]` creates a closure. A closure is just a token stream, so it can be stored in a variable, e.g. `[!set! #f = [!fn! ...]]`. Its body is not interpreted until the closure is called.
+//! * `[!call! #f x y]` calls the closure `#f` with the arguments `x` and `y`, giving the output `"x-y"` in this case. Each argument is a single token tree, variable or command, so if `#xs` is `a b`, then `[!call! #f #xs z]` binds `a b` to the first parameter. Wrap any other longer arguments in a group. The parameters are bound in a fresh scope, and the body is interpreted against the current state of all other variables, so `[!set! ...]` on an existing variable in the body updates that variable. Calls can be nested at most 64 deep, so a closure which calls itself without end results in an error.
+//!
 //! ### Concatenate and convert commands
 //!
 //! Each of these commands functions in three steps:
@@ -170,6 +179,143 @@
 //! >
 //! > A wide ranging set of tests covering behaviour are in [tests/string.rs](https://www.github.com/dhedey/preinterpret/blob/main/tests/string.rs).
 //!
+//! ### Literal commands
+//!
+//! These commands inspect or adjust a single literal, after interpreting their input. The numeric commands also accept a negative number, such as `-5i32`, which is a `-` punct followed by a literal.
+//!
+//! * `[!unsuffixed! 1usize]` outputs `1`. It removes the suffix from an integer or float literal, so works well with tuple field access such as `self.[!unsuffixed! #index]`. A float such as `2f64` becomes `2.0`.
+//! * `[!suffix_of! 1usize]` outputs the ident `usize`, or nothing if the literal has no suffix.
+//! * `[!with_suffix! 1usize u32]` outputs `1u32`. It replaces any suffix of the literal with the given suffix, and checks that an integer is in range for its new type.
+//! * `[!literal_value! b'x']` outputs the string literal `"x"`, and `[!literal_value! r#"a "quote""#]` outputs `"a \"quote\""`. It decodes a string, char, byte or byte string literal, including any escapes or raw string syntax, and outputs its value as a plain string literal. A byte or byte string literal must only contain ASCII bytes.
+//!
+//! ### Token stream commands
+//!
+//! Each of these commands first interprets its input, and then operates on the top-level token trees of the resulting stream. A group such as `(a b)` counts as a single token tree. Indices start from `0`, and an index outside the stream results in an error.
+//!
+//! * `[!skip! 2 from a b c d]` outputs `c d`
+//! * `[!take! 2 from a b c d]` outputs `a b`
+//! * `[!nth! 2 from a b c d]` outputs `c`
+//! * `[!first! a b c d]` outputs `a`
+//! * `[!last! a b c d]` outputs `d`
+//! * `[!slice! 1..3 from a b c d]` outputs `b c`. Ranges can also be inclusive such as `1..=3`, or unbounded such as `..3` or `1..`.
+//!
+//! The following commands measure a stream:
+//!
+//! * `[!len! a (b c) d]` outputs the unsuffixed integer literal `3`, the number of top-level token trees.
+//! * `[!count! , HashMap<K, V>, Vec<T>,]` outputs `2`. It counts the items separated by the separator (the first token tree), in the same way as `[!split! ..]` - so angle brackets are respected, and a trailing separator is permitted.
+//! * `[!is_empty! #stream]` outputs `true` if the interpreted stream has no token trees, else `false`.
+//!
+//! The following commands work with groups:
+//!
+//! * `[!ungroup! (a, b)]` outputs `a, b`. It expects its input to be a single group, and outputs the group's contents.
+//! * `[!group! brace a b]` outputs `{ a b }`. The first token is the delimiter: one of `paren`, `brace`, `bracket` or `none` (for a transparent group).
+//! * `[!regroup! bracket (a b)]` outputs `[a b]`. It expects a delimiter and then a single group, and changes the group's delimiter.
+//! * `[!delimiter_of! (a b)]` outputs the ident `paren`. It expects a single group, and outputs its delimiter, using the same names as `[!group! ..]`.
+//!
+//! The following commands work with separated lists:
+//!
+//! * `[!split! , HashMap<K, V>, Vec<T>,]` splits its input on each top-level occurrence of the separator (the first token tree), and outputs each segment wrapped in a transparent group - so this outputs two token trees, `HashMap<K, V>` and `Vec<T>`. Angle brackets are tracked, so separators inside generics don't cause a split - but note this means that a lone `<` comparison such as `a < b` may confuse it. The operators `<=`, `<<` and `<<=` are recognised, and don't. A trailing separator is permitted.
+//!
+//! * `[!join! + Clone Debug [!group! none Into<T>]]` outputs `Clone + Debug + Into<T>`. It outputs its items (the top-level token trees after the separator) with the separator between each, and no leading or trailing separator. Any item which is a transparent group is replaced by its contents, so this works well with the output of `[!split! ..]`, or items wrapped with `[!group! none ..]`. Other groups are kept, so `[!join! , (u8, u16) String]` outputs `(u8, u16), String`. If the separator is more than one token, wrap it in `(..)`, such as `[!join! (+ 'static +) ..]`.
+//! * `[!join_string! ", " a b c]` outputs `"a, b, c"`. It joins like `[!join! ..]`, and then concatenates the result into a string literal like `[!string! ..]`.
+//! * `[!intersperse! , (a, b) (c, d)]` outputs `(a, b), (c, d)`. It works like `[!join! ..]`, except the items are output as-is, without removing any transparent groups.
+//!
+//! The following commands find and replace tokens:
+//!
+//! * `[!replace! (Self) => (MyType) in fn new() -> Self { Self {} }]` outputs `fn new() -> MyType { MyType {} }`. The pattern and the replacement are each wrapped in a group such as `(..)`, so they can contain anything, including `=>` or `in`. The pattern can be any sequence of token trees, which is compared structurally, ignoring spacing. Each part is separated out before it is interpreted, so the pattern, replacement and stream can be variables such as `[!replace! (#from) => (#to) in #stream]`. Replacement happens inside groups at any depth.
+//! * `[!replace! top_level, (Self) => (MyType) in Self (Self)]` outputs `MyType (Self)`. With the `top_level,` flag, only top-level occurrences are replaced, leaving groups untouched.
+//!
+//! When a `macro_rules!` metavariable such as `$t:ty` or `$e:expr` is substituted into `preinterpret!`, it arrives wrapped in a transparent group (one with an invisible `none` delimiter). This means that the above commands see `$t` as a single token tree, even if it's `Vec<u8>`. To treat it the same as the literal tokens `Vec<u8>`, transparent groups can be removed:
+//!
+//! * `[!flatten! ...]` outputs its interpreted input, with each transparent group (at any depth) replaced by its contents.
+//! * `[!transparent_groups! strip]` outputs no tokens, and causes any transparent groups in the rest of the input to be replaced by their contents as they are interpreted. `[!transparent_groups! keep]` restores the default behaviour of keeping them.
+//!
+//! ### List commands
+//!
+//! These commands take one or more lists, each passed as a group such as `[a b c]` or `(#items)`. The items of a list are the top-level token trees inside its group. Each command first interprets its input.
+//!
+//! * `[!zip! [a b c] [1 2 3]]` outputs `(a 1) (b 2) (c 3)`. It takes any number of lists, which must all have the same length, and groups together the items at each index.
+//! * `[!cartesian! [A B] [X Y]]` outputs `(A X) (A Y) (B X) (B Y)`. It takes any number of lists, and outputs a group for each combination of their items. Like `[!range! ..]`, it can output at most 65536 groups.
+//!
+//! The following commands take a variable, a list and a `{ .. }` body. For each item of the list, the body is interpreted in a fresh scope, in which the variable is set to the item:
+//!
+//! * `[!map! #x in [a b] => { [!ident! get_ #x], }]` outputs `get_a, get_b,` - the concatenation of the interpreted bodies.
+//! * `[!filter! #x in [1 2 3 4] if { #x % 2 == 0 }]` outputs `2 4`. The body is evaluated like an [inline expression](#inline-expressions), and the item is output if it evaluates to `true`.
+//! * `[!fold! #acc = 0, #x in [1 2 3] => { #(#acc + #x) }]` outputs `6`. The accumulator `#acc` starts as the interpreted initial value, and is then replaced by the output of the body for each item in turn. The final accumulator is output.
+//!
+//! The following commands reorder or de-duplicate the top-level token trees of their interpreted input:
+//!
+//! * `[!sort! c a B]` outputs `B a c`, and `[!sort! 10 9]` outputs `9 10`. It sorts the token trees in the same way as the keys of `[!sort_by_key! ..]`.
+//! * `[!sort_by_key! #x in [(c 1) (a 3)] => { [!last! [!ungroup! #x]] }]` outputs `(c 1) (a 3)`. It sorts the items of the list by the key computed by the body, like `[!map! ..]`. Keys are compared numerically if they are both integer literals, and otherwise by their string form, as created by `[!string! ..]`. Integer keys come before any other keys.
+//! * `[!unique! a b a (c d) (c d)]` outputs `a b (c d)`. It keeps the first of any token trees which are equal, where equality is structural and ignores spans and spacing.
+//! * `[!reverse! a b c]` outputs `c b a`.
+//!
+//! The following commands treat lists as sets. Items are compared with the same structural equality as `[!unique! ..]`, and outputs keep the order in which items were first seen, without duplicates:
+//!
+//! * `[!union! [a b] [b c]]` outputs `a b c`. It takes any number of lists.
+//! * `[!intersection! [a b c] [c b d]]` outputs `b c`. It outputs the items of the first list which are in all the other lists.
+//! * `[!difference! [#fields] [#skipped_fields]]` outputs the items of the first list which aren't in any of the other lists.
+//! * `[!contains! [a b c] b]` outputs `true`. It takes a list and a single token tree, and outputs `true` if the list contains the token tree, else `false`.
+//!
+//! The following commands split their input into parenthesized groups of a given size. The size comes first, then any options, then `from` and the input. By default, the items are the top-level token trees of the input. With the `by <separator>` option, the items are instead separated by the given token tree, such as `by ,`, and the items in each group are separated by it too:
+//!
+//! * `[!chunks! 2 from a b c d e]` outputs `(a b) (c d) (e)`. The `remainder = keep|drop|error` option configures what happens to a final chunk which is too short, and defaults to `keep`. For example, `[!chunks! 2 by , remainder = drop from a, b, c]` outputs `(a, b)`.
+//! * `[!windows! 2 from a b c]` outputs `(a b) (b c)` - every run of consecutive items of the given size.
+//! * `[!prefixes! A B C]` outputs `(A) (A B) (A B C)`. It outputs a group for each prefix of the top-level token trees of its input, shortest first. This makes it easy to generate an impl for each size of tuple with `[!map! ..]`.
+//! * `[!suffixes! A B C]` outputs `(C) (B C) (A B C)`. It outputs a group for each suffix, shortest first.
+//!
+//! ### Integer commands
+//!
+//! * `[!range! 0..3]` outputs `0 1 2`. It also supports inclusive ranges such as `0..=3`, and a step such as `[!range! 0..10 step 3]`, which outputs `0 3 6 9`. If the start is greater than the end, it counts downwards, so `[!range! 3..0]` outputs `3 2 1`. Each bound is evaluated like an [inline expression](#inline-expressions), so `[!range! 0..#n + 1]` works. The output literals are unsuffixed, unless the bounds have a suffix, in which case they share it. Note that rust represents a negative integer as a `-` punct followed by a literal. It also supports char ranges such as `[!range! 'a'..='c']`, which outputs `'a' 'b' 'c'`, and which skip the surrogate code points `'\u{D800}'` to `'\u{DFFF}'`, as they aren't valid chars. A range can output at most 65536 values, so that a mistake such as an unexpectedly large bound errors rather than hanging the compiler.
+//!
+//! The following commands reformat an integer in another radix, which is useful for generating readable constants for bitflags or register maps. The integer is evaluated like an [inline expression](#inline-expressions), and can be followed by comma-separated options: `pad = N` pads with leading zeros to at least `N` digits, `group = N` separates every `N` digits with a `_`, and `string` outputs a string literal instead of an integer literal. Any suffix is kept on an integer literal output.
+//!
+//! * `[!hex! 65280, group = 2]` outputs `0xFF_00`, and `[!hex! 31, pad = 4, string]` outputs `"0x001F"`.
+//! * `[!bin! 10, pad = 8, group = 4]` outputs `0b0000_1010`.
+//! * `[!oct! 8]` outputs `0o10`.
+//!
+//! The following commands take a list of integers. Each is an integer literal, optionally negated with a `-`, or a `(..)` group containing an [inline expression](#inline-expressions), such as `(1 << #i)`. As in expressions, integers with a suffix are checked against the range of that type.
+//!
+//! * `[!bitand! 0b1100 0b1010]` outputs `8`, `[!bitor! 1 2 4]` outputs `7`, and `[!xor! 0xFF 0x0F]` outputs `240`. They take one or more integers. If any integer has a suffix, every integer must be in the range of that type, so `[!bitand! -1 0xFFu8]` is an error.
+//! * `[!shl! 1u32 31]` outputs `2147483648u32`. It errors if the shift is at least the number of bits in the type (or in an `i128`, if unsuffixed), or if any set bits are shifted out of the type. As in rust, a signed integer can be shifted into its sign bit, so `[!shl! 1i8 7]` outputs `-128i8`.
+//! * `[!shr! 0x80u8 7]` outputs `1u8`. It errors if the shift is at least the number of bits in the type.
+//! * `[!pow! 2 10]` outputs `1024`. The result keeps the type of the base, and errors if it overflows.
+//! * `[!bit_mask! 4..8]` outputs `240` (`0xF0`), a mask with the bits in the range set. It also supports inclusive ranges such as `4..=7`, and a suffix on the bounds, such as `0..8u8`, checks the range fits in that type. For a signed type, a mask including the sign bit is negative, so `[!bit_mask! 7..8i8]` outputs `-128i8`. As calculations use `i128` space, a `u128` mask can't include bit 127.
+//!
+//! The following aggregate commands also take a list of integers, and compute in `i128` space. This means that a `u128` value above `i128::MAX` is not supported, and results in an error. The result is unsuffixed, unless all the inputs share a suffix, in which case it keeps the suffix and is checked against the range of that type:
+//!
+//! * `[!min! 3 1 2]` outputs `1`, and `[!max! 3 1 2]` outputs `3`.
+//! * `[!sum! 1 2 3]` outputs `6`, and `[!product! 2 3 4]` outputs `24`. These work well with other commands, such as `[!sum! [!range! 1..=10]]`.
+//! * `[!abs! -5i32]` outputs `5i32`. It takes exactly one integer.
+//! * `[!clamp! #value 0 10]` outputs `#value`, restricted to be between `0` and `10` inclusive.
+//!
+//! ### Float commands
+//!
+//! These commands take a list of floats, each of which is a float or integer literal such as `1.5`, `2f32` or `3`, optionally negated with a `-`. They calculate at `f64` precision, or at `f32` precision if an input has an `f32` suffix, and keep any `f32` or `f64` suffix on their output. A result which is NaN or infinite is an error, as it can't be output as a literal.
+//!
+//! * `[!float_add! 1.5 2.25]` outputs `3.75`, and `[!float_mul! 1.5 2f32]` outputs `3.0f32`. They take one or more floats.
+//! * `[!float_sub! 1.5 2.5]` outputs `-1.0`, and `[!float_div! 1.0 4]` outputs `0.25`.
+//! * `[!float_sqrt! 2.0]`, `[!float_floor! 1.5]`, `[!float_ceil! 1.5]` and `[!float_round! 1.5]` take a single float. Like rust, `float_round` rounds half-way cases away from zero.
+//! * `[!float_format! 3.14159, precision = 2]` outputs `3.14`. It can be followed by comma-separated options: `precision = N` rounds to `N` decimal places, and `string` outputs a string literal such as `"3.14"` instead of a float literal.
+//!
+//! ### Predicate commands
+//!
+//! These commands interpret their input, which must be a single token tree, and output `true` or `false`. A transparent group around a single token tree, such as from a `$x:expr` substitution, is looked through. Note that a negative number such as `-1` is two token trees.
+//!
+//! * `[!is_ident! $x]`, `[!is_literal! $x]`, `[!is_group! $x]` and `[!is_punct! $x]` check the kind of the token tree.
+//! * `[!is_keyword! fn]` outputs `true`. It checks for a strict or reserved rust keyword, such as `fn`, `Self` or `async`. A raw identifier such as `r#type` is not a keyword.
+//! * `[!is_int! 42u8]`, `[!is_float! 4.2]` and `[!is_str! "hello"]` check for the given kind of literal, as parsed by `syn`. A byte string such as `b"hello"` is not a `str`.
+//! * `[!is_bool! true]` outputs `true`. It checks for `true` or `false`.
+//!
+//! ### Inline expressions
+//!
+//! `#( .. )` or `#{ .. }` interprets its contents, and then evaluates them as an expression over integer literals and `true` / `false`, outputting a single integer literal or boolean. For example, `#(#i + 1)` outputs `3` if `#i` is `2`, and `#{#len > 3 && #len % 2 == 0}` outputs `true` if `#len` is `4`.
+//!
+//! * The operators `+ - * / %`, `& | ^ << >>`, `== != < <= > >=`, `&& || !` and `(..)` grouping are supported, with rust precedence. Shifts error if they overflow the type. As in rust, comparisons can't be chained, so `1 < 2 < 3` is an error.
+//! * Integer literals can be written in any form rust supports, such as `0xFF`, `0b1010`, `0o17` or `1_000`.
+//! * Calculations operate in `i128` space. If any operand has a type suffix such as `5u8`, the result keeps the suffix and is checked against the range of that type. Operands with different suffixes result in an error.
+//! * Quote-style repetitions such as `#(#items)*` or `#(#items),*` are not treated as expressions, and are output as-is. So to multiply an expression, put the multiplication inside it: `#(#i * 2)`.
+//!
 //! ## Motivation
 //!
 //! ### Readability
@@ -280,7 +426,7 @@
 //! ```
 //!
 //! Now the `preinterpret!` macro runs, resulting in `#count` equal to the token stream `0usize + 1 + 1 + 1`.
-//! This will be improved in future releases by adding support for mathematical operations on integer literals.
+//! Alternatively, using an [inline expression](#inline-expressions) with `[!set! #current_index = #(#current_index + 1)]` evaluates the sum as it goes, giving `#count` equal to `3usize`. Or most simply, `[!len! $($item)*]` outputs `3` directly.
 //!
 //! ### Simplicity
 //!
@@ -294,6 +440,8 @@
 //!
 //! Standard use of preinterpret avoids this problem entirely, as demonstrated by the first readability example. If written out natively without preinterpret, the iteration of the generics in `#impl_generics` and `#my_type` wouldn't be compatible with the iteration over `$trait`.
 //!
+//! When you explicitly want every combination of two lists, the `[!cartesian! [$($trait)*] [$($type)*]]` command outputs each pair as a group.
+//!
 //! #### Eager macro confusion
 //!
 //! User-defined macros are not eager - they take a token stream in, and return a token stream; and further macros can then execute in this token stream.
@@ -416,24 +564,9 @@
 //!
 //! ### Possible extension: Integer commands
 //!
-//! Each of these commands functions in three steps:
-//! * Apply the interpreter to the token stream, which recursively executes preinterpret commands.
-//! * Iterate over each token (recursing into groups), expecting each to be an integer literal.
-//! * Apply some command-specific mapping to this stream of integer literals, and output a single integer literal without its type suffix. The suffix can be added back manually if required with a wrapper such as `[!literal! [!add! 1 2] u64]`.
-//!
-//! Integer commands under consideration are:
-//!
-//! * `[!add! 5u64 9 32]` outputs `46`. It takes any number of integers and outputs their sum. The calculation operates in `u128` space.
-//! * `[!sub! 64u32 1u32]` outputs `63`. It takes two integers and outputs their difference. The calculation operates in `i128` space.
-//! * `[!mod! $length 2]` outputs `0` if `$length` is even, else `1`. It takes two integers `a` and `b`, and outputs `a mod b`.
-//!
-//! We also support the following assignment commands:
+//! Integer arithmetic is covered by [inline expressions](#inline-expressions) such as `#(#i + 1)`, and by the [integer commands](#integer-commands). A shorthand assignment command is still under consideration:
 //!
-//! * `[!increment! #i]` is shorthand for `[!set! #i = [!add! #i 1]]` and outputs no tokens.
-//!
-//! Even better - we could even support calculator-style expression interpretation:
-//!
-//! * `[!usize! (5 + 10) / mod(4, 2)]` outputs `7usize`
+//! * `[!increment! #i]` is shorthand for `[!set! #i = #(#i + 1)]` and outputs no tokens.
 //!
 //! ### Possible extension: User-defined commands
 //!
@@ -441,27 +574,12 @@
 //!
 //! ### Possible extension: Boolean commands
 //!
-//! Each of these commands functions in three steps:
-//! * Apply the interpreter to the token stream, which recursively executes preinterpret commands.
-//! * Expects to read exactly two token trees (unless otherwise specified)
-//! * Apply some command-specific comparison, and outputs the boolean literal `true` or `false`.
-//!
-//! Comparison commands under consideration are:
+//! Integer comparisons and boolean logic are covered by [inline expressions](#inline-expressions) such as `#(#a <= #b && !#c)`. Comparison commands still under consideration are:
 //! * `[!eq! #foo #bar]` outputs `true` if `#foo` and `#bar` are exactly the same token tree, via structural equality. For example:
 //!   * `[!eq! (3 4) (3   4)]` outputs `true` because the token stream ignores spacing.
 //!   * `[!eq! 1u64 1]` outputs `false` because these are different literals.
-//! * `[!lt! #foo #bar]` outputs `true` if `#foo` is an integer literal and less than `#bar`
-//! * `[!gt! #foo #bar]` outputs `true` if `#foo` is an integer literal and greater than `#bar`
-//! * `[!lte! #foo #bar]` outputs `true` if `#foo` is an integer literal and less than or equal to `#bar`
-//! * `[!gte! #foo #bar]` outputs `true` if `#foo` is an integer literal and greater than or equal to `#bar`
-//! * `[!not! #foo]` expects a single boolean literal, and outputs the negation of `#foo`
 //! * `[!str_contains! "needle" [!string! haystack]]` expects two string literals, and outputs `true` if the first string is a substring of the second string.
 //!
-//! ### Possible extension: Token stream commands
-//!
-//! * `[!skip! 4 from [#stream]]` reads and drops the first 4 token trees from the stream, and outputs the rest
-//! * `[!ungroup! (#stream)]` outputs `#stream`. It expects to receive a single group (i.e. wrapped in brackets), and unwraps it.
-//!
 //! ### Possible extension: Control flow commands
 //!
 //! #### If statement
@@ -489,7 +607,7 @@
 //!     [!label! loop]
 //!     const [!ident! AB #i]: u8 = 0;
 //!     [!increment! #i]
-//!     [!if! [!lte! #i 100] then { [!goto! loop] }]
+//!     [!if! #(#i <= 100) then { [!goto! loop] }]
 //! }
 //! ```
 //!
@@ -512,10 +630,14 @@
 //!
 mod command;
 mod commands;
+#[cfg(test)]
+mod error_tests;
+mod expressions;
 mod internal_prelude;
 mod interpreter;
 mod parsing;
 mod string_conversion;
+mod token_helpers;
 
 use internal_prelude::*;
 
@@ -527,6 +649,7 @@ use internal_prelude::*;
 /// ## Command cheat sheet
 /// * `[!set! #foo = ...]` set a variable to the provided token stream
 /// * `#foo` outputs the variable's saved token stream
+/// * `#(#foo + 1)` evaluates an inline integer or boolean expression
 /// * `[!ident! ...]` outputs an ident from parsing the concatenated token stream
 /// * `[!ident_camel! ...]` outputs an UpperCamelCased ident from parsing the concatenated token stream
 /// * `[!ident_snake! ...]` outputs a lower_snake_cased ident from parsing the concatenated token stream
@@ -3,6 +3,7 @@ use crate::internal_prelude::*;
 pub(crate) enum NextItem {
     CommandInvocation(CommandInvocation),
     VariableSubstitution(VariableSubstitution),
+    InlineExpression(InlineExpression),
     Group(Group),
     Leaf(TokenTree),
     EndOfStream,
@@ -21,6 +22,9 @@ pub(crate) fn parse_next_item(tokens: &mut Tokens) -> Result<NextItem> {
             if let Some(variable_substitution) = parse_only_if_variable_substitution(&punct, tokens)
             {
                 NextItem::VariableSubstitution(variable_substitution)
+            } else if let Some(inline_expression) = parse_only_if_inline_expression(&punct, tokens)
+            {
+                NextItem::InlineExpression(inline_expression)
             } else {
                 NextItem::Leaf(TokenTree::Punct(punct))
             }
@@ -41,6 +45,120 @@ pub(crate) fn parse_variable(tokens: &mut Tokens) -> Option<Ident> {
     tokens.next_as_ident()
 }
 
+/// Consumes tokens up to and including the first top-level lone `char` punct (i.e. not part of
+/// a multi-character punct such as `==` or `=>`), and returns the tokens before it.
+/// Returns `None` if no such punct exists.
+pub(crate) fn parse_until_punct(tokens: &mut Tokens, char: char) -> Option<TokenStream> {
+    let mut preceding = TokenStream::new();
+    let mut previous_was_joint = false;
+    loop {
+        let token_tree = tokens.next()?;
+        if let TokenTree::Punct(punct) = &token_tree {
+            if punct.as_char() == char && punct.spacing() == Spacing::Alone && !previous_was_joint {
+                return Some(preceding);
+            }
+            previous_was_joint = punct.spacing() == Spacing::Joint;
+        } else {
+            previous_was_joint = false;
+        }
+        preceding.extend(iter::once(token_tree));
+    }
+}
+
+//===========================================
+// Helpers for parsing interpreted arguments
+//===========================================
+
+/// Parses a non-negative integer literal, such as an index or a count.
+pub(crate) fn parse_usize(token_tree: Option<TokenTree>, span: Span) -> Result<usize> {
+    let literal = match token_tree {
+        Some(TokenTree::Literal(literal)) => literal,
+        Some(token_tree) => {
+            return Err(token_tree.span().error(format!(
+                "Expected a non-negative integer literal, but found `{}`",
+                token_tree
+            )))
+        }
+        None => {
+            return Err(span.error("Expected a non-negative integer literal, but the input ended"))
+        }
+    };
+    match Lit::new(literal.clone()) {
+        Lit::Int(lit_int) => lit_int.base10_parse::<usize>(),
+        _ => Err(literal.span().error(format!(
+            "Expected a non-negative integer literal, but found `{}`",
+            literal
+        ))),
+    }
+}
+
+/// Consumes the given keyword ident, or errors.
+pub(crate) fn parse_keyword(tokens: &mut Tokens, keyword: &str, span: Span) -> Result<()> {
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident == keyword => Ok(()),
+        Some(token_tree) => Err(token_tree.span().error(format!(
+            "Expected `{}`, but found `{}`",
+            keyword, token_tree
+        ))),
+        None => Err(span.error(format!("Expected `{}`, but the input ended", keyword))),
+    }
+}
+
+/// The bounds of a range such as `a..b`, `a..=b`, `a..` or `..b`, where each bound is the
+/// (possibly empty) token stream either side of the `..`.
+pub(crate) struct RangeBounds {
+    pub(crate) start: TokenStream,
+    pub(crate) end: TokenStream,
+    pub(crate) inclusive: bool,
+}
+
+/// Parses a range, whose end bound continues until the end of the stream or until one of the
+/// `terminators` idents, which isn't consumed.
+pub(crate) fn parse_range(
+    tokens: &mut Tokens,
+    terminators: &[&str],
+    span: Span,
+) -> Result<RangeBounds> {
+    let mut start = TokenStream::new();
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(punct))
+                if punct.as_char() == '.' && punct.spacing() == Spacing::Joint =>
+            {
+                match tokens.next() {
+                    Some(TokenTree::Punct(second)) if second.as_char() == '.' => break,
+                    _ => return Err(punct.span().error("Expected a range such as `a..b`")),
+                }
+            }
+            Some(token_tree) => start.extend(iter::once(token_tree)),
+            None => return Err(span.error("Expected a range such as `a..b`")),
+        }
+    }
+    let inclusive = match tokens.peek() {
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '=' => {
+            tokens.next();
+            true
+        }
+        _ => false,
+    };
+    let mut end = TokenStream::new();
+    loop {
+        match tokens.peek() {
+            Some(TokenTree::Ident(ident)) if terminators.iter().any(|t| ident == t) => break,
+            Some(_) => end.extend(tokens.next()),
+            None => break,
+        }
+    }
+    if inclusive && end.is_empty() {
+        return Err(span.error("An inclusive range `a..=b` requires an end bound"));
+    }
+    Ok(RangeBounds {
+        start,
+        end,
+        inclusive,
+    })
+}
+
 fn parse_command_invocation(group: &Group) -> Result<Option<CommandInvocation>> {
     fn consume_command_start(group: &Group) -> Option<(Ident, Tokens)> {
         if group.delimiter() != Delimiter::Bracket {
@@ -99,3 +217,26 @@ fn parse_only_if_variable_substitution(
         _ => unreachable!("We just peeked a token of this type"),
     }
 }
+
+// We ensure we don't consume any tokens unless we have an inline expression
+fn parse_only_if_inline_expression(punct: &Punct, tokens: &mut Tokens) -> Option<InlineExpression> {
+    if punct.as_char() != '#' {
+        return None;
+    }
+    match tokens.peek() {
+        Some(TokenTree::Group(group))
+            if matches!(group.delimiter(), Delimiter::Parenthesis | Delimiter::Brace) => {}
+        _ => return None,
+    }
+    // Quote-style repetitions such as `#(#items)*` or `#(#items),*` are left alone
+    let is_star = |token_tree: Option<TokenTree>| matches!(token_tree, Some(TokenTree::Punct(punct)) if punct.as_char() == '*');
+    let is_punct = |token_tree: Option<TokenTree>| matches!(token_tree, Some(TokenTree::Punct(_)));
+    if is_star(tokens.peek_nth(1)) || (is_punct(tokens.peek_nth(1)) && is_star(tokens.peek_nth(2)))
+    {
+        return None;
+    }
+    match tokens.next() {
+        Some(TokenTree::Group(group)) => Some(InlineExpression::new(group)),
+        _ => unreachable!("We just peeked a token of this type"),
+    }
+}
@@ -0,0 +1,138 @@
+use crate::internal_prelude::*;
+
+/// Interprets the argument, and returns its top-level token trees.
+pub(crate) fn interpret_to_token_trees(
+    interpreter: &mut Interpreter,
+    argument: CommandArgumentStream,
+) -> Result<Vec<TokenTree>> {
+    Ok(argument.interpret(interpreter)?.into_iter().collect())
+}
+
+/// Compares two token trees structurally, ignoring spans and spacing between tokens.
+pub(crate) fn token_trees_eq(left: &TokenTree, right: &TokenTree) -> bool {
+    match (left, right) {
+        (TokenTree::Group(left), TokenTree::Group(right)) => {
+            left.delimiter() == right.delimiter() && token_streams_eq(left.stream(), right.stream())
+        }
+        (TokenTree::Ident(left), TokenTree::Ident(right)) => left == right,
+        (TokenTree::Punct(left), TokenTree::Punct(right)) => left.as_char() == right.as_char(),
+        (TokenTree::Literal(left), TokenTree::Literal(right)) => {
+            left.to_string() == right.to_string()
+        }
+        _ => false,
+    }
+}
+
+/// Compares two token streams structurally, ignoring spans and spacing between tokens.
+pub(crate) fn token_streams_eq(left: TokenStream, right: TokenStream) -> bool {
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+    loop {
+        match (left.next(), right.next()) {
+            (Some(left), Some(right)) if token_trees_eq(&left, &right) => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Splits the token trees on each top-level occurrence of the separator.
+///
+/// As `<` and `>` are puncts rather than group delimiters, their nesting is tracked so that
+/// separators inside generics such as `HashMap<K, V>` are skipped. Arrows such as `->` and `=>`
+/// aren't counted as closing angle brackets, and the operators `<=`, `<<` and `<<=` aren't
+/// counted as opening angle brackets.
+///
+/// A trailing separator is permitted, and doesn't create an empty final segment.
+pub(crate) fn split_top_level(
+    token_trees: impl IntoIterator<Item = TokenTree>,
+    separator: &TokenTree,
+) -> Vec<TokenStream> {
+    let mut segments = Vec::new();
+    let mut current = TokenStream::new();
+    let mut angle_depth: usize = 0;
+    let mut previous_joint_punct: Option<char> = None;
+    let mut token_trees = token_trees.into_iter().peekable();
+    while let Some(token_tree) = token_trees.next() {
+        if angle_depth == 0 && token_trees_eq(&token_tree, separator) {
+            segments.push(std::mem::take(&mut current));
+            previous_joint_punct = None;
+            continue;
+        }
+        if let TokenTree::Punct(punct) = &token_tree {
+            match punct.as_char() {
+                '<' => {
+                    let is_operator = previous_joint_punct == Some('<')
+                        || (punct.spacing() == Spacing::Joint
+                            && matches!(
+                                token_trees.peek(),
+                                Some(TokenTree::Punct(next)) if matches!(next.as_char(), '=' | '<')
+                            ));
+                    if !is_operator {
+                        angle_depth += 1;
+                    }
+                }
+                '>' if !matches!(previous_joint_punct, Some('-') | Some('=')) => {
+                    angle_depth = angle_depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+            previous_joint_punct = match punct.spacing() {
+                Spacing::Joint => Some(punct.as_char()),
+                Spacing::Alone => None,
+            };
+        } else {
+            previous_joint_punct = None;
+        }
+        current.extend(iter::once(token_tree));
+    }
+    // Skip the final segment if it's empty, as it follows a trailing separator (or no input)
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+/// Expects the stream to be a single token tree.
+///
+/// A transparent group around a single token tree, such as is created by substituting a `$x:expr`
+/// or `$x:literal` fragment, is looked through.
+pub(crate) fn parse_single_token_tree(stream: TokenStream, span: Span) -> Result<TokenTree> {
+    let mut token_trees = stream.into_iter();
+    match (token_trees.next(), token_trees.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::None => {
+            let mut inner = group.stream().into_iter();
+            match (inner.next(), inner.next()) {
+                (Some(token_tree), None) => Ok(token_tree),
+                _ => Ok(TokenTree::Group(group)),
+            }
+        }
+        (Some(token_tree), None) => Ok(token_tree),
+        (None, _) => Err(span.error("Expected a single token tree, but the input was empty")),
+        (Some(_), Some(extra)) => Err(extra.span().error(
+            "Expected a single token tree, but found more than one. A negative number such as `-1` is two token trees.",
+        )),
+    }
+}
+
+/// Replaces each transparent group (with [`Delimiter::None`]) by its contents, at every depth.
+pub(crate) fn flatten_transparent_groups(stream: TokenStream) -> TokenStream {
+    let mut output = TokenStream::new();
+    for token_tree in stream {
+        match token_tree {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                output.extend(flatten_transparent_groups(group.stream()));
+            }
+            TokenTree::Group(group) => {
+                let mut flattened = Group::new(
+                    group.delimiter(),
+                    flatten_transparent_groups(group.stream()),
+                );
+                flattened.set_span(group.span());
+                output.extend(iter::once(TokenTree::Group(flattened)));
+            }
+            token_tree => output.extend(iter::once(token_tree)),
+        }
+    }
+    output
+}
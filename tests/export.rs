@@ -0,0 +1,23 @@
+use preinterpret::preinterpret;
+
+preinterpret! {
+    [!set! #greeting = "Hello"]
+    [!set! #raw_value = [!raw! #not_a_variable]]
+    [!set! #shout = [!fn! (#x) { [!upper! #x] }]]
+    [!set_lazy! #message = [!string! #greeting ", " #name "!"]]
+    [!export! my_definitions = #greeting #raw_value #shout #message]
+}
+
+my_definitions! {
+    [!set! #name = World]
+    const MESSAGE: &str = #message;
+    const RAW_VALUE: &str = [!string! #raw_value];
+    const SHOUTED: &str = [!call! #shout #greeting];
+}
+
+#[test]
+fn test_exported_variables_are_set() {
+    assert_eq!(MESSAGE, "Hello, World!");
+    assert_eq!(RAW_VALUE, "#not_a_variable");
+    assert_eq!(SHOUTED, "HELLO");
+}
@@ -0,0 +1,59 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_integer_arithmetic() {
+    my_assert_eq!({ #(1 + 2 * 3) }, 7);
+    my_assert_eq!({ #((1 + 2) * 3) }, 9);
+    my_assert_eq!({ #(17 / 5 - 17 % 5) }, 1);
+    my_assert_eq!({ #(-3 - -4) }, 1);
+    my_assert_eq!({ #(10 - 2 - 3) }, 5);
+    my_assert_eq!({ #{200u8 + 55} }, 255u8);
+    my_assert_eq!({ #(-128i8) }, -128i8);
+    my_assert_eq!({
+        [!set! #i = 4]
+        #(#i * #i + 1)
+    }, 17);
+}
+
+#[test]
+fn test_boolean_logic() {
+    my_assert_eq!({ #(1 < 2) }, true);
+    my_assert_eq!({ #(3 >= 4) }, false);
+    my_assert_eq!({ #(1 + 1 == 2 && 3 != 3) }, false);
+    my_assert_eq!({ #(true || false && false) }, true);
+    my_assert_eq!({ #(!(1 <= 0) == true) }, true);
+    my_assert_eq!({ #((1 < 2) == (3 < 4) && 1 < 2) }, true);
+    my_assert_eq!({
+        [!set! #len = 5]
+        #{#len > 3}
+    }, true);
+}
+
+#[test]
+fn test_expressions_compose_with_commands() {
+    my_assert_eq!([!string! "Item " #(0 - 5)], "Item -5");
+    my_assert_eq!({
+        [!set! #i = 2]
+        [!set! #i = #(#i + 1)]
+        [!string! x_ #i]
+    }, "x_3");
+}
+
+#[test]
+fn test_repetitions_are_left_alone() {
+    my_assert_eq!([!string! [!raw! #(#a)*] " " #(1) * 2], "#(#a)* #(1)*2");
+    my_assert_eq!([!string! #(1),*], "#(1),*");
+}
+
+#[test]
+fn test_expressions_not_followed_by_a_repetition_operator() {
+    my_assert_eq!([!string! #(1) + 2], "1+2");
+    my_assert_eq!([!string! #(1), 2], "1,2");
+    my_assert_eq!([!string! #(1) 2 * 3], "12*3");
+}
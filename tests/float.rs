@@ -0,0 +1,64 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_float_arithmetic() {
+    my_assert_eq!([!float_add! 1.5 2.25], 3.75);
+    my_assert_eq!([!float_add! 1.5 2 0.5], 4.0);
+    my_assert_eq!([!float_sub! 1.5 2.5], -1.0);
+    my_assert_eq!([!float_mul! 1.5 -2.0], -3.0);
+    my_assert_eq!([!float_div! 1.0 4], 0.25);
+    my_assert_eq!([!float_sqrt! 2.25], 1.5);
+    my_assert_eq!([!float_floor! -1.5], -2.0);
+    my_assert_eq!([!float_ceil! 1.2], 2.0);
+    my_assert_eq!([!float_round! 2.5], 3.0);
+    my_assert_eq!([!float_round! -2.5], -3.0);
+    my_assert_eq!([!float_mul! 1e300 1e5], 1e300 * 1e5);
+}
+
+#[test]
+fn test_float_suffixes() {
+    my_assert_eq!([!string! [!float_add! 1.5f32 2]], "3.5f32");
+    my_assert_eq!([!string! [!float_mul! 2f64 3f64]], "6.0f64");
+    my_assert_eq!([!string! [!float_add! 1.5 2]], "3.5");
+    my_assert_eq!([!float_div! 1f32 3f32], 1f32 / 3f32);
+    my_assert_eq!([!float_add! 0.1f32 0.2f32], 0.1f32 + 0.2f32);
+    my_assert_eq!([!float_add! 0.1 0.2], 0.1 + 0.2);
+}
+
+#[test]
+fn test_float_format() {
+    my_assert_eq!([!string! [!float_format! 3.14159, precision = 2]], "3.14");
+    my_assert_eq!([!float_format! 3.14159, precision = 2, string], "3.14");
+    my_assert_eq!(
+        [!string! [!float_format! 2.5f32, precision = 3]],
+        "2.500f32"
+    );
+    my_assert_eq!([!string! [!float_format! 2.7, precision = 0]], "3.0");
+    my_assert_eq!([!float_format! 2.7, precision = 0, string], "3");
+    my_assert_eq!([!string! [!float_format! -0.001, precision = 1]], "0.0");
+    my_assert_eq!([!float_format! -0.001, precision = 1, string], "0.0");
+    my_assert_eq!([!float_format! -1.26, precision = 1, string], "-1.3");
+    my_assert_eq!([!float_format! 1.5, string], "1.5");
+    my_assert_eq!([!float_format! -1.5f32, string], "-1.5");
+    my_assert_eq!([!string! [!float_format! 1.5f32]], "1.5f32");
+    my_assert_eq!(
+        [!float_format! [!float_div! 1.0 3.0], precision = 4],
+        0.3333
+    );
+}
+
+#[test]
+fn test_float_lookup_table() {
+    preinterpret! {
+        const RECIPROCALS: [f64; 4] = [
+            [!join! , [!map! #i in [[!range! 1..=4]] => { [!float_div! 1.0 #i] }]]
+        ];
+    }
+    assert_eq!(RECIPROCALS, [1.0, 0.5, 1.0 / 3.0, 0.25]);
+}
@@ -0,0 +1,162 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_range() {
+    my_assert_eq!([!string! [!intersperse! , [!range! 0..5]]], "0,1,2,3,4");
+    my_assert_eq!([!string! [!intersperse! , [!range! 0..=5]]], "0,1,2,3,4,5");
+    my_assert_eq!(
+        [!string! [!intersperse! , [!range! 0..10 step 3]]],
+        "0,3,6,9"
+    );
+    my_assert_eq!([!string! [!intersperse! , [!range! 5..0]]], "5,4,3,2,1");
+    my_assert_eq!([!string! [!range! 3..=-3 step 2]], "31-1-3");
+    my_assert_eq!([!string! [!range! 0..0]], "");
+    my_assert_eq!({
+        [!set! #n = 3]
+        [!string! [!intersperse! , [!range! 1..#n + 1]]]
+    }, "1,2,3");
+    my_assert_eq!([!string! [!intersperse! , [!range! 0u8..3]]], "0u8,1u8,2u8");
+    my_assert_eq!(
+        [!string! [!intersperse! , [!range! 254u8..=255u8]]],
+        "254u8,255u8"
+    );
+    my_assert_eq!([!string! [!range! 'a'..='e']], "abcde");
+    my_assert_eq!([!string! [!range! 'a'..'z' step 5]], "afkpu");
+    my_assert_eq!([!string! [!range! 'c'..='a']], "cba");
+    my_assert_eq!([!string![!ident! [!range! 'x'..='z']]], "xyz");
+    // The surrogate code points aren't chars, so are skipped
+    my_assert_eq!(
+        [!string! [!range! '\u{D7FF}'..='\u{E000}']],
+        "\u{D7FF}\u{E000}"
+    );
+    my_assert_eq!([!len! [!range! 0u16..=65535u16]], 65536);
+    my_assert_eq!([!string! [!range! 0..3 step 1u8]], "0u81u82u8");
+}
+
+#[test]
+fn test_range_generates_idents() {
+    preinterpret! {
+        [!set! #fields = [!map! #i in [[!range! 0..3]] => { [!ident! field_ #i] }]]
+        struct Fields { [!join! , [!map! #f in [#fields] => { [!group! none #f: usize] }]] }
+        let fields = Fields { [!join! , [!map! #f in [#fields] => { [!group! none #f: 1] }]] };
+        assert_eq!(fields.field_0 + fields.field_1 + fields.field_2, 3);
+    }
+}
+
+#[test]
+fn test_radix_formatting() {
+    my_assert_eq!([!hex! 255], 0xFF);
+    my_assert_eq!([!string! [!hex! 255]], "0xFF");
+    my_assert_eq!([!string! [!hex! 255u16]], "0xFFu16");
+    my_assert_eq!([!string! [!hex! 65280, group = 2]], "0xFF_00");
+    my_assert_eq!([!string! [!hex! 31, pad = 4]], "0x001F");
+    my_assert_eq!([!hex! 31, string], "0x1F");
+    my_assert_eq!([!hex! 31u8, pad = 4, group = 2, string], "0x00_1F");
+    my_assert_eq!([!string! [!bin! 10, pad = 8, group = 4]], "0b0000_1010");
+    my_assert_eq!([!bin! 5], 0b101);
+    my_assert_eq!([!oct! 8, string], "0o10");
+    my_assert_eq!([!string! [!hex! 4 * 4]], "0x10");
+    my_assert_eq!([!string! [!hex! 0]], "0x0");
+}
+
+#[test]
+fn test_expressions_accept_radix_literals() {
+    my_assert_eq!({ #(0xFF + 0b1 + 0o7 + 1_000) }, 1263);
+    my_assert_eq!({ #(0xFF_u8 - 0x0F) }, 0xF0u8);
+    my_assert_eq!([!string! [!hex! 0b1111_0000]], "0xF0");
+    my_assert_eq!([!string! [!range! 0x0A..0x0D]], "101112");
+}
+
+#[test]
+fn test_bitwise_expressions() {
+    my_assert_eq!({ #(1 << 4) }, 16);
+    my_assert_eq!({ #(256 >> 4) }, 16);
+    my_assert_eq!({ #(0b1100 & 0b1010) }, 0b1000);
+    my_assert_eq!({ #(0b1100 | 0b1010) }, 0b1110);
+    my_assert_eq!({ #(0b1100 ^ 0b1010) }, 0b0110);
+    // Rust precedence: shifts bind more loosely than `+`, and `&` more tightly than `|`
+    my_assert_eq!({ #(1 << 2 + 1) }, 8);
+    my_assert_eq!({ #(1 | 6 & 3) }, 3);
+    my_assert_eq!({ #(1 << 3 == 8) }, true);
+    my_assert_eq!({ #(1u8 << 7u32) }, 128u8);
+    // Like in rust, a signed integer can be shifted into its sign bit
+    my_assert_eq!({ #(1i8 << 7) }, -128i8);
+    my_assert_eq!({ #(-1i8 << 7) }, -128i8);
+    my_assert_eq!({ #(true ^ false) }, true);
+}
+
+#[test]
+fn test_bitwise_commands() {
+    my_assert_eq!([!bitand! 0b1100 0b1010 0b1000], 0b1000);
+    my_assert_eq!([!bitor! 1 2 4 (1 << 3)], 15);
+    my_assert_eq!(
+        [!bitor! [!map! #i in [[!range! 0..4]] => { (1 << #i) }]],
+        15
+    );
+    my_assert_eq!([!xor! 0xFFu8 0x0F], 0xF0u8);
+    my_assert_eq!([!bitand! -1 0xFF], 0xFF);
+    my_assert_eq!([!shl! 1u32 31], 1u32 << 31);
+    my_assert_eq!([!shl! 3i16 14], 3i16 << 14);
+    my_assert_eq!([!shl! 1i128 127], i128::MIN);
+    my_assert_eq!([!shr! 0x80u8 7], 1u8);
+    my_assert_eq!([!shr! -16 2], -4);
+    my_assert_eq!([!pow! 2 10], 1024);
+    my_assert_eq!([!pow! 3u8 5], 243u8);
+}
+
+#[test]
+fn test_bit_mask() {
+    my_assert_eq!([!bit_mask! 4..8], 0xF0);
+    my_assert_eq!([!bit_mask! 4..=7], 0xF0);
+    my_assert_eq!([!bit_mask! 0..8u8], 0xFFu8);
+    my_assert_eq!([!bit_mask! 0..64u64], u64::MAX);
+    my_assert_eq!([!bit_mask! 0..127u128], i128::MAX as u128);
+    my_assert_eq!([!bit_mask! 0..8i8], -1i8);
+    my_assert_eq!([!bit_mask! 7..8i8], i8::MIN);
+    my_assert_eq!([!bit_mask! 4..8i8], 0xF0u8 as i8);
+    my_assert_eq!([!bit_mask! 0..128i128], -1i128);
+    my_assert_eq!([!bit_mask! 3..3], 0);
+    my_assert_eq!([!string![!hex! [!bit_mask! 8..16], group = 2]], "0xFF_00");
+}
+
+#[test]
+fn test_aggregates() {
+    my_assert_eq!([!min! 3 1 2], 1);
+    my_assert_eq!([!max! 3 1 2], 3);
+    my_assert_eq!([!min! 5 -7 2], -7);
+    my_assert_eq!([!sum! 1 2 3], 6);
+    my_assert_eq!([!sum! [!range! 1..=100]], 5050);
+    my_assert_eq!([!product! 2 3 4], 24);
+    my_assert_eq!([!product! 7], 7);
+    my_assert_eq!([!abs! -5], 5);
+    my_assert_eq!([!abs!(3 - 10)], 7);
+    my_assert_eq!([!clamp! 15 0 10], 10);
+    my_assert_eq!([!clamp! -5 0 10], 0);
+    my_assert_eq!([!clamp! 5 0 10], 5);
+}
+
+#[test]
+fn test_aggregate_suffixes() {
+    my_assert_eq!([!string! [!sum! 1u8 2u8]], "3u8");
+    my_assert_eq!([!string! [!sum! 1u8 2]], "3");
+    my_assert_eq!([!string! [!max! 1u8 2u16]], "2");
+    my_assert_eq!([!string! [!abs! -5i32]], "5i32");
+    my_assert_eq!([!string! [!clamp! 300u16 0u16 255u16]], "255u16");
+}
+
+#[test]
+fn test_max_discriminant() {
+    preinterpret! {
+        [!set! #discriminants = 3 10 7]
+        const MAX_DISCRIMINANT: u32 = [!max! #discriminants];
+        const TOTAL: u32 = [!sum! #discriminants];
+    }
+    assert_eq!(MAX_DISCRIMINANT, 10);
+    assert_eq!(TOTAL, 20);
+}
@@ -0,0 +1,168 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_zip() {
+    my_assert_eq!([!string! [!zip! [a b c] [1 2 3]]], "(a1)(b2)(c3)");
+    my_assert_eq!([!string! [!zip! [a b] (1 2) {x y}]], "(a1x)(b2y)");
+    my_assert_eq!([!string! [!zip! [(a b) c] [1 2]]], "((ab)1)(c2)");
+    my_assert_eq!([!string! [!zip! [] []]], "");
+}
+
+#[test]
+fn test_cartesian() {
+    my_assert_eq!([!string! [!cartesian! [A B] [X Y]]], "(AX)(AY)(BX)(BY)");
+    my_assert_eq!(
+        [!string! [!cartesian! [A] [X Y] [1 2]]],
+        "(AX1)(AX2)(AY1)(AY2)"
+    );
+    my_assert_eq!([!string! [!cartesian! [A B] []]], "");
+}
+
+#[test]
+fn test_map() {
+    my_assert_eq!(
+        [!string! [!map! #x in [a b c] => { [!ident! #x _field], }]],
+        "a_field,b_field,c_field,"
+    );
+    my_assert_eq!({
+        [!set! #items = 1 2 3]
+        [!set! #offset = 10]
+        [!string! [!map! #x in [#items] => { #(#x + #offset) " " }]]
+    }, "11 12 13 ");
+    my_assert_eq!(
+        [!string! [!map! #pair in [[!zip! [a b] [1 2]]] => { [!join! = [!ungroup! #pair]]; }]],
+        "a=1;b=2;"
+    );
+    my_assert_eq!({
+        [!set! #x = outer]
+        [!map! #x in [1 2] => {}]
+        [!string! #x]
+    }, "outer");
+}
+
+#[test]
+fn test_filter() {
+    my_assert_eq!(
+        [!string! [!filter! #x in [1 2 3 4 5 6] if { #x % 2 == 0 }]],
+        "246"
+    );
+    my_assert_eq!({
+        [!set! #min = 3]
+        [!string! [!filter! #x in [1 5 2 7] if { #x >= #min }]]
+    }, "57");
+}
+
+#[test]
+fn test_fold() {
+    my_assert_eq!([!fold! #acc = 0, #x in [1 2 3 4] => { #(#acc + #x) }], 10);
+    my_assert_eq!(
+        [!string! [!fold! #acc = start, #x in [a b] => { (#acc #x) }]],
+        "((starta)b)"
+    );
+    my_assert_eq!([!string! [!fold! #acc = none, #x in [] => { #x }]], "none");
+}
+
+#[test]
+fn test_sort_and_reverse() {
+    my_assert_eq!(
+        [!string! [!sort! Charlie alpha Bravo "b" 10 9]],
+        "910BravoCharliealphab"
+    );
+    my_assert_eq!([!string! [!sort! 100 9u8 0x10 10]], "9u8100x10100");
+    my_assert_eq!(
+        [!string! [!sort_by_key! #x in [10 9 100 0] => { #x }]],
+        "0910100"
+    );
+    my_assert_eq!(
+        [!string! [!sort_by_key! #x in [(c 1) (a 3) (b 2)] => { [!last! [!ungroup! #x]] }]],
+        "(c1)(b2)(a3)"
+    );
+    my_assert_eq!(
+        [!string! [!sort_by_key! #x in [Bb a Ccc] => { [!lower! #x] }]],
+        "aBbCcc"
+    );
+    my_assert_eq!([!string! [!reverse! a (b c) d]], "d(bc)a");
+}
+
+#[test]
+fn test_unique() {
+    my_assert_eq!(
+        [!string! [!unique! a b a (c  d) (c d) 1 1u8 b]],
+        "ab(cd)11u8"
+    );
+}
+
+#[test]
+fn test_set_operations() {
+    my_assert_eq!([!string! [!union! [a b a] [c b] [d]]], "abcd");
+    my_assert_eq!([!string! [!intersection! [a b c b] [c b d] [b c]]], "bc");
+    my_assert_eq!({
+        [!set! #fields = id name email password]
+        [!set! #skipped = password id]
+        [!string! [!difference! [#fields] [#skipped]]]
+    }, "nameemail");
+    my_assert_eq!([!string! [!difference! [(a b) (c d)] [(c  d)]]], "(ab)");
+    my_assert_eq!([!contains! [a b c] b], true);
+    my_assert_eq!([!contains! [a b c] d], false);
+    my_assert_eq!([!contains! [(x y) 1u8] 1], false);
+    my_assert_eq!([!contains! [(x y) 1u8] (x y)], true);
+}
+
+#[test]
+fn test_chunks() {
+    my_assert_eq!([!string! [!chunks! 2 from a b c d e]], "(ab)(cd)(e)");
+    my_assert_eq!(
+        [!string! [!chunks! 2 remainder = drop from a b c d e]],
+        "(ab)(cd)"
+    );
+    my_assert_eq!(
+        [!string! [!chunks! 2 remainder = error from a b c d]],
+        "(ab)(cd)"
+    );
+    my_assert_eq!(
+        [!string! [!chunks! 2 by , from a, B<C, D>, e,]],
+        "(a,B<C,D>)(e)"
+    );
+    my_assert_eq!(
+        [!string! [!chunks! 3 by , remainder = drop from 1, 2, 3, 4]],
+        "(1,2,3)"
+    );
+    my_assert_eq!([!string! [!chunks! 2 from]], "");
+}
+
+#[test]
+fn test_windows() {
+    my_assert_eq!([!string! [!windows! 2 from a b c d]], "(ab)(bc)(cd)");
+    my_assert_eq!([!string! [!windows! 3 from a b]], "");
+    my_assert_eq!(
+        [!string! [!windows! 2 by , from x, Y<Z, W>, v]],
+        "(x,Y<Z,W>)(Y<Z,W>,v)"
+    );
+}
+
+#[test]
+fn test_prefixes_and_suffixes() {
+    my_assert_eq!([!string! [!prefixes! A B C]], "(A)(AB)(ABC)");
+    my_assert_eq!([!string! [!suffixes! A B C]], "(C)(BC)(ABC)");
+    my_assert_eq!([!string! [!prefixes!]], "");
+
+    trait Arity {
+        const ARITY: usize;
+    }
+    preinterpret::preinterpret! {
+        [!map! #params in [[!prefixes! A B C]] => {
+            [!set! #params = [!intersperse! , [!ungroup! #params]]]
+            impl<#params> Arity for (#params,) {
+                const ARITY: usize = [!count! , #params];
+            }
+        }]
+    }
+    assert_eq!(<(u8,) as Arity>::ARITY, 1);
+    assert_eq!(<(u8, u8, u8) as Arity>::ARITY, 3);
+}
@@ -44,3 +44,46 @@ fn test_character() {
 fn test_byte_character() {
     my_assert_eq!([!literal! "b'a'"], b'a');
 }
+
+#[test]
+fn test_unsuffixed() {
+    my_assert_eq!([!unsuffixed! 0usize], 0);
+    my_assert_eq!([!string! [!unsuffixed! 0xFFu8]], "0xFF");
+    my_assert_eq!([!string! [!unsuffixed! -5i32]], "-5");
+    my_assert_eq!([!string! [!unsuffixed! 1.5f32]], "1.5");
+    my_assert_eq!([!string! [!unsuffixed! 2f64]], "2.0");
+    my_assert_eq!([!string! [!unsuffixed! 7]], "7");
+    let tuple = (1, 2);
+    my_assert_eq!({
+        [!set! #index = 1usize]
+        tuple.[!unsuffixed! #index]
+    }, 2);
+}
+
+#[test]
+fn test_suffix_of() {
+    my_assert_eq!([!string! [!suffix_of! 0usize]], "usize");
+    my_assert_eq!([!string! [!suffix_of! 1.0f32]], "f32");
+    my_assert_eq!([!string! [!suffix_of! 0]], "");
+    preinterpret!(let _: [!suffix_of! 5u16] = 5u16;);
+}
+
+#[test]
+fn test_with_suffix() {
+    my_assert_eq!([!string! [!with_suffix! 5usize u32]], "5u32");
+    my_assert_eq!([!string! [!with_suffix! 5 i8]], "5i8");
+    my_assert_eq!([!string! [!with_suffix! -5 i8]], "-5i8");
+    my_assert_eq!([!string! [!with_suffix! 0xFF u8]], "0xFFu8");
+    my_assert_eq!([!string! [!with_suffix! 2 f32]], "2f32");
+    my_assert_eq!([!string! [!with_suffix! 2.5f32 f64]], "2.5f64");
+    my_assert_eq!([!with_suffix! 255 u8], 255u8);
+}
+
+#[test]
+fn test_literal_value() {
+    my_assert_eq!([!literal_value! "hello\tworld"], "hello\tworld");
+    my_assert_eq!([!literal_value! r#"raw "quoted""#], "raw \"quoted\"");
+    my_assert_eq!([!literal_value! 'x'], "x");
+    my_assert_eq!([!literal_value! b'x'], "x");
+    my_assert_eq!([!literal_value! b"bytes\n"], "bytes\n");
+}
@@ -0,0 +1,70 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_token_tree_kind_predicates() {
+    my_assert_eq!([!is_ident! foo], true);
+    my_assert_eq!([!is_ident! 1], false);
+    my_assert_eq!([!is_literal! "hello"], true);
+    my_assert_eq!([!is_literal! hello], false);
+    my_assert_eq!([!is_group! (a b)], true);
+    my_assert_eq!([!is_group! a], false);
+    my_assert_eq!([!is_punct! +], true);
+    my_assert_eq!([!is_punct![a]], false);
+    my_assert_eq!({
+        [!set! #x = Foo]
+        [!is_ident! #x]
+    }, true);
+}
+
+#[test]
+fn test_keyword_predicate() {
+    my_assert_eq!([!is_keyword! fn], true);
+    my_assert_eq!([!is_keyword! Self], true);
+    my_assert_eq!([!is_keyword! async], true);
+    my_assert_eq!([!is_keyword! r#type], false);
+    my_assert_eq!([!is_keyword! foo], false);
+    my_assert_eq!([!is_keyword! "fn"], false);
+}
+
+#[test]
+fn test_literal_predicates() {
+    my_assert_eq!([!is_int! 42], true);
+    my_assert_eq!([!is_int! 0xFFu8], true);
+    my_assert_eq!([!is_int! 4.2], false);
+    my_assert_eq!([!is_float! 4.2], true);
+    my_assert_eq!([!is_float! 1e3f32], true);
+    my_assert_eq!([!is_float! 42], false);
+    my_assert_eq!([!is_str! "hello"], true);
+    my_assert_eq!([!is_str! r"raw"], true);
+    my_assert_eq!([!is_str! b"bytes"], false);
+    my_assert_eq!([!is_str! 'c'], false);
+    my_assert_eq!([!is_bool! true], true);
+    my_assert_eq!([!is_bool! false], true);
+    my_assert_eq!([!is_bool! "true"], false);
+    my_assert_eq!([!is_int! foo], false);
+}
+
+#[test]
+fn test_predicates_on_macro_fragments() {
+    macro_rules! describe {
+        ($x:tt) => {
+            preinterpret!([!string! [!is_literal! $x] [!is_ident! $x]])
+        };
+    }
+    assert_eq!(describe!(5), "truefalse");
+    assert_eq!(describe!(five), "falsetrue");
+
+    macro_rules! is_int_expr {
+        ($x:expr) => {
+            preinterpret!([!string! [!is_int! $x]])
+        };
+    }
+    assert_eq!(is_int_expr!(5), "true");
+    assert_eq!(is_int_expr!("five"), "false");
+}
@@ -0,0 +1,171 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_stream_slicing() {
+    my_assert_eq!([!string! [!skip! 2 from a b (c d) e]], "(cd)e");
+    my_assert_eq!([!string! [!skip! 4 from a b (c d) e]], "");
+    my_assert_eq!([!string! [!take! 2 from a b (c d) e]], "ab");
+    my_assert_eq!([!string! [!nth! 2 from a b (c d) e]], "(cd)");
+    my_assert_eq!([!string! [!first! a b c]], "a");
+    my_assert_eq!([!string! [!last! a b c]], "c");
+    my_assert_eq!([!string! [!slice! 1..3 from a b c d]], "bc");
+    my_assert_eq!([!string! [!slice! 1..=3 from a b c d]], "bcd");
+    my_assert_eq!([!string! [!slice! ..2 from a b c d]], "ab");
+    my_assert_eq!([!string! [!slice! 2.. from a b c d]], "cd");
+    my_assert_eq!({
+        [!set! #stream = 1 2 3 4 5]
+        [!set! #n = 3]
+        [!nth! #(#n - 1) from #stream]
+    }, 3);
+}
+
+#[test]
+fn test_group_manipulation() {
+    my_assert_eq!([!string![!ungroup!(a, b, c)]], "a,b,c");
+    my_assert_eq!([!ungroup! { 1 + 2 }], 3);
+    my_assert_eq!([!string! [!group! brace a b]], "{ab}");
+    my_assert_eq!([!string! [!group! none a b]], "ab");
+    my_assert_eq!([!string! [!regroup! bracket (a b)]], "[ab]");
+    my_assert_eq!({ [!regroup! paren { 1 + 2 }] * 2 }, 6);
+    my_assert_eq!([!string! [!delimiter_of! (a)] " " [!delimiter_of! {}] " " [!delimiter_of! [!group! none x]]], "paren brace none");
+}
+
+macro_rules! first_token_of_type {
+    ($t:ty) => {
+        preinterpret!([!string! [!first! $t]])
+    };
+}
+
+macro_rules! first_token_of_flattened_type {
+    ($t:ty) => {
+        preinterpret!([!string! [!first! [!flatten! $t]]])
+    };
+}
+
+macro_rules! first_token_of_type_with_stripping {
+    ($t:ty) => {
+        preinterpret!({
+            [!transparent_groups! strip]
+            [!string! [!first! $t]]
+        })
+    };
+}
+
+#[test]
+fn test_transparent_groups() {
+    assert_eq!(first_token_of_type!(Vec<u8>), "Vec<u8>");
+    assert_eq!(first_token_of_flattened_type!(Vec<u8>), "Vec");
+    assert_eq!(first_token_of_type_with_stripping!(Vec<u8>), "Vec");
+    my_assert_eq!([!string![!first! [!flatten! [!group! none a b] c]]], "a");
+}
+
+#[test]
+fn test_split() {
+    my_assert_eq!(
+        [!string! [!nth! 0 from [!split! , HashMap<K, V>, Vec<T>]]],
+        "HashMap<K,V>"
+    );
+    my_assert_eq!(
+        [!string! [!nth! 1 from [!split! , HashMap<K, V>, Vec<T>]]],
+        "Vec<T>"
+    );
+    my_assert_eq!([!string![!last! [!split! , A, B<C<D, E>>, F,]]], "F");
+    my_assert_eq!(
+        [!string! [!nth! 1 from [!split! , A, B<C<D, E>>, F,]]],
+        "B<C<D,E>>"
+    );
+    my_assert_eq!(
+        [!string![!last! [!split! , fn(X) -> Y, impl Fn(Z) -> W]]],
+        "implFn(Z)->W"
+    );
+    my_assert_eq!(
+        [!string! [!regroup! bracket [!nth! 1 from [!split! ; a b; c d; e]]]],
+        "[cd]"
+    );
+    my_assert_eq!([!string![!delimiter_of![!first! [!split! , a, b]]]], "none");
+    my_assert_eq!([!string! [!split! ,]], "");
+    // Comparison and shift operators aren't angle brackets
+    my_assert_eq!([!string![!last! [!split! , a <= b, c]]], "c");
+    my_assert_eq!([!string![!last! [!split! , x << 1, y]]], "y");
+    my_assert_eq!([!string![!last! [!split! , x <<= 1, y]]], "y");
+    // A lone `<` is assumed to open generics
+    my_assert_eq!([!string![!last! [!split! , a < b, c]]], "a<b,c");
+    my_assert_eq!([!count! , x << 1, y], 2);
+}
+
+#[test]
+fn test_join_and_intersperse() {
+    my_assert_eq!({
+        [!set! #items = 1 2 3]
+        [!join! + #items]
+    }, 6);
+    my_assert_eq!(
+        [!string! [!join! (+ 'static +) Clone [!group! none Into<T>] Debug]],
+        "Clone+'static+Into<T>+'static+Debug"
+    );
+    my_assert_eq!([!string! [!join! ; [!split! , A<B, C>, D]]], "A<B,C>;D");
+    my_assert_eq!([!string! [!join! ,]], "");
+    my_assert_eq!([!string! [!intersperse! , (a, b) (c, d)]], "(a,b),(c,d)");
+    my_assert_eq!([!join_string! ", " a (b c) "d"], "a, (bc), d");
+    my_assert_eq!(
+        [!string! [!join! , (u8, u16) [u8; 2] String]],
+        "(u8,u16),[u8;2],String"
+    );
+    my_assert_eq!([!join_string! ", " [!split! , Vec<u8>, HashMap<K, V>]], "Vec<u8>, HashMap<K,V>");
+}
+
+#[test]
+fn test_replace() {
+    my_assert_eq!(
+        [
+            !string! [!replace! (Self) => (MyType) in fn new() -> Self { Self { x: <Self as Default>::default() } }]
+        ],
+        "fnnew()->MyType{MyType{x:<MyTypeasDefault>::default()}}"
+    );
+    my_assert_eq!(
+        [!string! [!replace! top_level, (Self) => (MyType) in Self (Self)]],
+        "MyType(Self)"
+    );
+    my_assert_eq!(
+        [!string! [!replace! (a b) => (c) in a b a (a b) b a]],
+        "ca(c)ba"
+    );
+    my_assert_eq!([!string! [!replace! ((x)) => (y) in (x) ((x))]], "y(y)");
+    my_assert_eq!(
+        [!string! [!replace! (a) => (for x in y) in a b]],
+        "forxinyb"
+    );
+    my_assert_eq!({
+        [!set! #placeholder = __T]
+        [!set! #concrete = Vec<u8>]
+        [!string! [!replace! (#placeholder) => (#concrete) in Option<__T>]]
+    }, "Option<Vec<u8>>");
+}
+
+macro_rules! count_idents {
+    ($($item:ident),*) => {
+        preinterpret!([!len! $($item)*])
+    };
+}
+
+#[test]
+fn test_len_and_count() {
+    assert_eq!(count_idents!(), 0);
+    assert_eq!(count_idents!(a, b, c), 3);
+    my_assert_eq!([!len! a (b c) [d e f] 1u64], 4);
+    my_assert_eq!([!len! [!split! , HashMap<K, V>, Vec<T>]], 2);
+    my_assert_eq!([!count! , HashMap<K, V>, Vec<T>], 2);
+    my_assert_eq!([!count! , HashMap<K, V>, Vec<T>,], 2);
+    my_assert_eq!([!count! , fn(A, B) -> C, Box<dyn Fn(D) -> E>], 2);
+    my_assert_eq!([!count! ,], 0);
+    my_assert_eq!([!count! ; a; b c; d], 3);
+    my_assert_eq!([!is_empty!], true);
+    my_assert_eq!([!is_empty! [!skip! 2 from a b]], true);
+    my_assert_eq!([!is_empty! a], false);
+}
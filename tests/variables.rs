@@ -0,0 +1,68 @@
+use preinterpret::preinterpret;
+
+macro_rules! my_assert_eq {
+    ($input:tt, $($output:tt)*) => {
+        assert_eq!(preinterpret!($input), $($output)*);
+    };
+}
+
+#[test]
+fn test_set_named_and_get() {
+    my_assert_eq!({
+        [!set_named! field_ 0 _ty = u32]
+        [!set! #index = 0]
+        [!string! [!get! field_ #index _ty]]
+    }, "u32");
+    my_assert_eq!({
+        [!set! #prefix = my]
+        [!set_named! #prefix _var = "Hello"]
+        #my_var
+    }, "Hello");
+    my_assert_eq!({
+        [!set! #x = 1]
+        [!set_named! [!ident! x] = #x + 2]
+        [!string! [!get! "x"]]
+    }, "1+2");
+}
+
+#[test]
+fn test_set_lazy() {
+    my_assert_eq!({
+        [!set! #prefix = Hello]
+        [!set! #name = World]
+        [!set_lazy! #full_name = [!string! #prefix " " #name]]
+        [!set! #first = #full_name]
+        [!set! #name = Rust]
+        [!string! #first ", " #full_name]
+    }, "Hello World, Hello Rust");
+    my_assert_eq!({
+        [!set! #inner = 1]
+        [!set_lazy! #a = #inner]
+        [!set_lazy! #b = #a #a]
+        [!set! #inner = 2]
+        [!string! #b]
+    }, "22");
+}
+
+#[test]
+fn test_closures() {
+    my_assert_eq!({
+        [!set! #greet = [!fn! (#greeting #name) { [!string! #greeting ", " #name #punctuation] }]]
+        [!set! #punctuation = "!"]
+        [!string! [!call! #greet Hello World] " " [!call! #greet "Goodbye" [!ident! Rust]]]
+    }, "Hello, World! Goodbye, Rust!");
+    my_assert_eq!({
+        [!set! #x = outer]
+        [!set! #count = 0]
+        [!set! #f = [!fn! (#x) { [!set! #count = #count #x] }]]
+        [!call! #f 1]
+        [!call! #f 2]
+        [!string! #x ":" #count]
+    }, "outer:012");
+    // A variable or command is a single argument, even if its value is several token trees
+    my_assert_eq!({
+        [!set! #pair = [!fn! (#first #second) { [!string! "[" #first "|" #second "]"] }]]
+        [!set! #xs = a b]
+        [!string! [!call! #pair #xs [!group! paren c d]] " " [!call! #pair [!ungroup! (x y)] z]]
+    }, "[ab|(cd)] [xy|z]");
+}